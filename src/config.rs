@@ -0,0 +1,643 @@
+use crossterm::cursor::SetCursorStyle;
+use crossterm::event::{KeyCode, KeyModifiers};
+use ratatui::style::{Color, Modifier, Style};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::keys::KeySpec;
+use crate::{Focus, PreformError, preform_dir};
+
+/// Settings read from `.pre-form-git/config.toml`. Every key is optional.
+#[derive(Debug, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    /// Another TOML file, e.g. in a submodule shared across an org, whose
+    /// settings apply unless this file sets them too. Relative paths are
+    /// resolved from the including file; it may include further files.
+    pub config_include: Option<PathBuf>,
+    /// `false` turns pre-form off in this repo (as does `.pre-form-git/disabled`).
+    pub enabled: bool,
+    /// Prefilled into the Body input whenever the app starts with an empty body.
+    pub body_template: Option<String>,
+    /// Prefilled into the Footer on startup; `{branch}`, `{ticket}` and
+    /// `{date}` are replaced (see `template::Variables`).
+    pub footer_template: Option<String>,
+    /// Squeeze runs of spaces in the scope and description to one space.
+    pub collapse_spaces: bool,
+    /// How the written message file ends: `single` (default) or `none`.
+    pub trailing_newline: TrailingNewline,
+    /// How the scope sits in the header: `()` (default), `[]` or `/`.
+    pub scope_format: ScopeFormat,
+    /// Add a scope typed into the Scope field to `scopes.txt` once a message
+    /// using it is written; aborting leaves the file alone.
+    pub save_typed_scopes: bool,
+    /// Write the draft once typing has paused this long, in milliseconds
+    /// (default 1000).
+    pub autosave_idle_ms: u64,
+    /// Also write the draft after this many keys of steady typing.
+    pub autosave_edits: Option<u32>,
+    /// Spelling scopes are held to (`lower` or `kebab`); unset leaves them be.
+    pub scope_case: Option<ScopeCase>,
+    /// Written in place of a missing scope, e.g. `*` for `feat(*): ...`;
+    /// unset leaves the scope out.
+    pub empty_scope_placeholder: Option<String>,
+    /// Re-flow body lines to this many columns when writing the message.
+    pub body_wrap_width: Option<usize>,
+    /// Write a blank line before a list or heading that follows body prose.
+    pub separate_body_blocks: bool,
+    /// Move on from the Type list once a type is picked: with Space on the
+    /// list, or Enter in the `/` filter.
+    pub auto_advance: bool,
+    /// Enter writes the message from any field, as the confirm key does,
+    /// instead of moving to the next field (a new line in the Body) and writing
+    /// only from the last one.
+    pub enter_writes: bool,
+    /// Writes the message from any field (default `f2`).
+    pub confirm_key: KeySpec,
+    /// Takes the dim scope suggestion shown after the cursor (default `right`).
+    pub accept_suggestion_key: KeySpec,
+    /// Field focused on startup (`type`, `scope`, `description`, `body`, `footer`).
+    pub default_focus: Option<Focus>,
+    /// Enter the header on a single line instead of the full form (`--compact`).
+    pub compact: bool,
+    /// Just a subject and a body, written verbatim: no type, scope or footer,
+    /// for repos that don't use Conventional Commits (`--freeform`).
+    pub freeform: bool,
+    /// Print a one-line summary of the written message (`--quiet` turns it off).
+    pub summary: bool,
+    /// Take over the whole screen; `false` renders inline below the prompt.
+    pub alternate_screen: bool,
+    /// Preview the subject as `git log --oneline` shows it, greyed past this
+    /// many characters; `0` hides the preview.
+    pub subject_preview_width: usize,
+    /// Put between the header, body and footer; newlines only.
+    pub section_separator: String,
+    /// Longest header, in characters, that may be written.
+    pub header_max_length: Option<usize>,
+    /// At least one footer line must match this regex before the message is written.
+    pub require_footer_pattern: Option<String>,
+    /// A ticket this regex matches at the start of the description is moved
+    /// to the Ticket field or a `Refs:` footer line on confirm.
+    pub description_ticket_pattern: Option<String>,
+    /// End the footer with a `Branch: <name>` trailer for the checked-out branch.
+    pub include_branch_footer: bool,
+    /// Environment variables whose values prefill footer trailers, mapped to
+    /// the trailer token, e.g. `GERRIT_CHANGE_ID = "Change-Id"`.
+    pub env_trailers: BTreeMap<String, String>,
+    /// Tags such as `[skip ci]` that Ctrl+T cycles through to add to the message.
+    pub automation_tags: Vec<String>,
+    /// Where an automation tag goes: after the description or as the last
+    /// footer line.
+    pub automation_tag_placement: TagPlacement,
+    /// A breaking change needs a body or a `BREAKING CHANGE:` footer saying why.
+    pub require_breaking_explanation: bool,
+    /// Open the TUI for `git commit -m` too, starting from the given text.
+    pub format_inline_messages: bool,
+    /// Open the TUI for `git commit --amend` too, starting from the commit's
+    /// message. Also opens for `--amend --no-edit`, which a hook can't tell apart.
+    pub edit_amends: bool,
+    /// Suggest splitting the commit when staged files span more top-level
+    /// directories than this; `0` turns the hint off.
+    pub split_hint_dirs: usize,
+    /// List the staged hunks with their enclosing functions under the staged
+    /// changes panel.
+    pub show_hunks: bool,
+    /// Shell command Ctrl+P pipes the message to, e.g. `glow -`.
+    pub preview_cmd: Option<String>,
+    /// Header layout with `{type}`, `{emoji}`, `{scope}`, `{breaking}` and
+    /// `{description}`, e.g. `{emoji} {description}`; unset writes
+    /// `type(scope): description`.
+    pub header_template: Option<String>,
+    /// File with the layout of the whole message, its placeholders filled from
+    /// the form; relative to the working directory, which is the repository
+    /// root when Git runs the hook.
+    pub commit_template: Option<PathBuf>,
+    /// Show at most this many rows in the Type list (5 when unset) and the
+    /// pickers; longer lists scroll.
+    pub max_visible_items: Option<usize>,
+    /// Named text snippets inserted at the cursor by a key (`[snippets.reviewed]`).
+    pub snippets: BTreeMap<String, Snippet>,
+    /// Changelog section each type ends up in (`feat = "Features"`), shown next
+    /// to the selected type.
+    pub changelog_sections: BTreeMap<String, String>,
+    /// Named presets for recurring kinds of commit (`[intents.hotfix]`),
+    /// picked when the form opens or with `--intent`.
+    pub intents: BTreeMap<String, Intent>,
+    /// Per-type settings, keyed by type name (`[types.feat]`).
+    pub types: BTreeMap<String, TypeConfig>,
+    /// Look of the selected row and the cursor (`[theme]`).
+    pub theme: Theme,
+    /// Adds a Ticket field to the form (`[ticket]`); unset hides it.
+    pub ticket: Option<TicketConfig>,
+    /// Length limits shown as `n/max` in the field titles (`[max_length]`).
+    pub max_length: BTreeMap<LimitedField, FieldLimit>,
+    /// How strictly each rule is enforced (`[severity]`); unlisted rules block.
+    pub severity: BTreeMap<Rule, Severity>,
+    /// `require_footer_pattern`, compiled on load.
+    #[serde(skip)]
+    pub footer_regex: Option<Regex>,
+    /// `description_ticket_pattern`, compiled on load.
+    #[serde(skip)]
+    pub description_ticket_regex: Option<Regex>,
+    /// The contents of `commit_template`, read on load.
+    #[serde(skip)]
+    pub commit_template_text: Option<String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct TicketConfig {
+    /// A ticket, when given, must match this regex.
+    pub pattern: Option<String>,
+    /// Where the ticket goes in the message.
+    pub placement: TicketPlacement,
+    /// How the ticket is written; `{ticket}` is replaced. Defaults to
+    /// `Refs: {ticket}` in the footer and `[{ticket}] ` before the description.
+    pub format: Option<String>,
+    /// `pattern`, compiled on load.
+    #[serde(skip)]
+    pub regex: Option<Regex>,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TicketPlacement {
+    /// A trailer after the other footer lines.
+    #[default]
+    Footer,
+    /// A prefix to the description.
+    Description,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TagPlacement {
+    /// Appended to the description, after a space.
+    #[default]
+    Header,
+    /// A line of its own at the end of the footer.
+    Footer,
+}
+
+impl TicketConfig {
+    /// `ticket` as it appears in the message.
+    pub fn render(&self, ticket: &str) -> String {
+        let format = self.format.as_deref().unwrap_or(match self.placement {
+            TicketPlacement::Footer => "Refs: {ticket}",
+            TicketPlacement::Description => "[{ticket}] ",
+        });
+        format.replace("{ticket}", ticket)
+    }
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            config_include: None,
+            enabled: true,
+            body_template: None,
+            footer_template: None,
+            collapse_spaces: false,
+            trailing_newline: TrailingNewline::default(),
+            scope_format: ScopeFormat::default(),
+            save_typed_scopes: true,
+            autosave_idle_ms: 1000,
+            autosave_edits: None,
+            scope_case: None,
+            empty_scope_placeholder: None,
+            body_wrap_width: None,
+            separate_body_blocks: false,
+            auto_advance: false,
+            enter_writes: false,
+            confirm_key: KeySpec {
+                modifiers: KeyModifiers::NONE,
+                code: KeyCode::F(2),
+            },
+            accept_suggestion_key: KeySpec {
+                modifiers: KeyModifiers::NONE,
+                code: KeyCode::Right,
+            },
+            default_focus: None,
+            compact: false,
+            freeform: false,
+            summary: true,
+            alternate_screen: true,
+            subject_preview_width: 50,
+            section_separator: "\n\n".to_string(),
+            header_max_length: None,
+            require_footer_pattern: None,
+            description_ticket_pattern: None,
+            require_breaking_explanation: false,
+            include_branch_footer: false,
+            env_trailers: BTreeMap::new(),
+            automation_tags: vec!["[skip ci]".to_string()],
+            automation_tag_placement: TagPlacement::default(),
+            format_inline_messages: false,
+            edit_amends: false,
+            split_hint_dirs: 4,
+            show_hunks: false,
+            preview_cmd: None,
+            header_template: None,
+            commit_template: None,
+            commit_template_text: None,
+            max_visible_items: None,
+            snippets: BTreeMap::new(),
+            changelog_sections: BTreeMap::new(),
+            intents: BTreeMap::new(),
+            types: BTreeMap::new(),
+            theme: Theme::default(),
+            ticket: None,
+            max_length: BTreeMap::new(),
+            severity: BTreeMap::new(),
+            footer_regex: None,
+            description_ticket_regex: None,
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, Deserialize)]
+#[serde(default)]
+pub struct TypeConfig {
+    /// Shown next to the type; a component file's contents take precedence.
+    pub description: Option<String>,
+    /// Heading the type is listed under in the Type list.
+    pub group: Option<String>,
+    /// Refuse to write the message without a scope.
+    pub require_scope: bool,
+    /// One-line commits: hide the Body and leave it out of the message.
+    pub forbid_body: bool,
+    /// Hide the Footer and leave it out of the message.
+    pub forbid_footer: bool,
+    /// Gitmoji for the `{emoji}` placeholder of `header_template`.
+    pub emoji: Option<String>,
+    /// The only scopes this type may be written with; empty allows any.
+    pub allowed_scopes: Vec<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(default)]
+pub struct Theme {
+    /// Drawn before the selected row of a list.
+    pub highlight_symbol: String,
+    /// Color of the selected row: a name such as `cyan`, or `#rrggbb`.
+    pub highlight_color: Option<String>,
+    /// Draw the selected row in bold.
+    pub highlight_bold: bool,
+    /// Cursor shape while the form is open; unset keeps the terminal's own.
+    pub cursor: Option<CursorShape>,
+    /// `highlight_color` and `highlight_bold`, resolved on load.
+    #[serde(skip)]
+    pub highlight_style: Style,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Theme {
+            // plain ASCII renders on every terminal
+            highlight_symbol: "> ".to_string(),
+            highlight_color: None,
+            highlight_bold: true,
+            cursor: None,
+            highlight_style: Style::default().add_modifier(Modifier::BOLD),
+        }
+    }
+}
+
+/// How `trailing_newline` ends the message file.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TrailingNewline {
+    /// Exactly one newline, as Git itself writes messages.
+    #[default]
+    Single,
+    /// No newline after the last line.
+    None,
+}
+
+/// How `scope_format` puts the scope after the type.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize)]
+pub enum ScopeFormat {
+    /// `feat(api): `, the Conventional Commits form.
+    #[default]
+    #[serde(rename = "()")]
+    Parens,
+    /// `feat[api]: `
+    #[serde(rename = "[]")]
+    Brackets,
+    /// `feat/api: `
+    #[serde(rename = "/")]
+    Slash,
+}
+
+impl ScopeFormat {
+    /// `scope` as it follows the type; nothing when it is empty.
+    pub fn wrap(self, scope: &str) -> String {
+        if scope.is_empty() {
+            return String::new();
+        }
+        match self {
+            ScopeFormat::Parens => format!("({scope})"),
+            ScopeFormat::Brackets => format!("[{scope}]"),
+            ScopeFormat::Slash => format!("/{scope}"),
+        }
+    }
+
+    /// The characters `wrap` puts around a scope; the slash has no closing one.
+    pub fn delimiters(self) -> (char, Option<char>) {
+        match self {
+            ScopeFormat::Parens => ('(', Some(')')),
+            ScopeFormat::Brackets => ('[', Some(']')),
+            ScopeFormat::Slash => ('/', None),
+        }
+    }
+}
+
+/// How `scope_case` spells scopes.
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ScopeCase {
+    /// `Api` becomes `api`.
+    Lower,
+    /// `ApiClient` and `api_client` become `api-client`.
+    Kebab,
+}
+
+/// A check `[severity]` can tune.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Rule {
+    /// A type's `require_scope`.
+    RequireScope,
+    /// A type's `allowed_scopes`.
+    AllowedScopes,
+    /// The `[ticket]` pattern.
+    TicketFormat,
+    /// `header_max_length`.
+    HeaderLength,
+    /// Hard `[max_length]` limits.
+    MaxLength,
+    /// `require_footer_pattern`.
+    FooterFormat,
+    /// `require_breaking_explanation`.
+    BreakingExplanation,
+}
+
+/// What a broken rule does.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    /// Refuse to write the message.
+    #[default]
+    Error,
+    /// Show the problem but write the message anyway.
+    Warn,
+    /// Don't check.
+    Off,
+}
+
+/// A field that `[max_length]` can limit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LimitedField {
+    Scope,
+    Description,
+    /// Each line of the body on its own.
+    BodyLine,
+}
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+pub struct FieldLimit {
+    /// Characters allowed.
+    pub max: usize,
+    /// Refuse typing past `max`; otherwise the counter only turns red.
+    #[serde(default)]
+    pub hard: bool,
+}
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CursorShape {
+    Block,
+    Underline,
+    Bar,
+}
+
+impl CursorShape {
+    pub fn style(self) -> SetCursorStyle {
+        match self {
+            CursorShape::Block => SetCursorStyle::SteadyBlock,
+            CursorShape::Underline => SetCursorStyle::SteadyUnderScore,
+            CursorShape::Bar => SetCursorStyle::SteadyBar,
+        }
+    }
+}
+
+/// Fields an intent presets; the ones left out keep their value.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct Intent {
+    /// Shown next to the name in the picker.
+    pub description: Option<String>,
+    #[serde(rename = "type")]
+    pub commit_type: Option<String>,
+    pub scope: Option<String>,
+    pub breaking: Option<bool>,
+    pub body: Option<String>,
+    /// `{branch}`, `{ticket}` and `{date}` are replaced as in `footer_template`.
+    pub footer: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Snippet {
+    pub key: KeySpec,
+    /// Inserted as-is; the cursor lands on `{cursor}` if present, else after it.
+    pub text: String,
+}
+
+pub fn config_file() -> PathBuf {
+    preform_dir().join("config.toml")
+}
+
+impl Config {
+    /// How `rule` is enforced; `error` unless configured otherwise.
+    pub fn severity(&self, rule: Rule) -> Severity {
+        self.severity.get(&rule).copied().unwrap_or_default()
+    }
+
+    pub fn load() -> Result<Config, PreformError> {
+        let path = config_file();
+        if !path.exists() {
+            return Ok(Config::default());
+        }
+        let table = load_table(&path, &mut Vec::new())?;
+        let mut config: Config = toml::Value::Table(table)
+            .try_into()
+            .map_err(|source| PreformError::ConfigParse { path, source })?;
+        if let Some(pattern) = &config.require_footer_pattern {
+            config.footer_regex = Some(compile("require_footer_pattern", pattern)?);
+        }
+        if let Some(template) = &config.commit_template {
+            let text = fs::read_to_string(template).map_err(PreformError::io(template))?;
+            config.commit_template_text = Some(text);
+        }
+        if let Some(pattern) = &config.description_ticket_pattern {
+            config.description_ticket_regex = Some(compile("description_ticket_pattern", pattern)?);
+        }
+        let separator = &config.section_separator;
+        if separator.is_empty() || separator.chars().any(|c| c != '\n') {
+            return Err(PreformError::Validation {
+                setting: "section_separator".to_string(),
+                message: format!("must be one or more newlines, not {separator:?}"),
+            });
+        }
+        if let Some(ticket) = &mut config.ticket
+            && let Some(pattern) = &ticket.pattern
+        {
+            ticket.regex = Some(compile("ticket.pattern", pattern)?);
+        }
+        let theme = &mut config.theme;
+        let mut style = Style::default();
+        if let Some(color) = &theme.highlight_color {
+            let color: Color = color.parse().map_err(|_| PreformError::Validation {
+                setting: "theme.highlight_color".to_string(),
+                message: format!("unknown color `{color}`"),
+            })?;
+            style = style.fg(color);
+        }
+        if theme.highlight_bold {
+            style = style.add_modifier(Modifier::BOLD);
+        }
+        theme.highlight_style = style;
+        Ok(config)
+    }
+}
+
+/// The settings in `path` laid over those of its `config_include`, if any.
+/// `seen` holds the files already being read; a file included again, in a
+/// cycle, adds nothing the second time.
+fn load_table(path: &Path, seen: &mut Vec<PathBuf>) -> Result<toml::Table, PreformError> {
+    let canonical = path.canonicalize().map_err(PreformError::io(path))?;
+    if seen.contains(&canonical) {
+        eprintln!(
+            "pre-form: `{}` is included in a cycle; not reading it again",
+            path.display()
+        );
+        return Ok(toml::Table::new());
+    }
+    seen.push(canonical);
+    let raw = fs::read_to_string(path).map_err(PreformError::io(path))?;
+    let table: toml::Table = raw.parse().map_err(|source| PreformError::ConfigParse {
+        path: path.to_path_buf(),
+        source,
+    })?;
+    let Some(include) = table.get("config_include") else {
+        return Ok(table);
+    };
+    let include = include.as_str().ok_or_else(|| PreformError::Validation {
+        setting: "config_include".to_string(),
+        message: format!("expected a path in `{}`", path.display()),
+    })?;
+    let dir = path.parent().unwrap_or(Path::new("."));
+    let mut merged = load_table(&dir.join(include), seen)?;
+    merge_tables(&mut merged, table);
+    Ok(merged)
+}
+
+/// Lay `over` onto `base`: nested tables merge key by key, other values replace.
+fn merge_tables(base: &mut toml::Table, over: toml::Table) {
+    for (key, value) in over {
+        match (base.get_mut(&key), value) {
+            (Some(toml::Value::Table(base)), toml::Value::Table(over)) => merge_tables(base, over),
+            (_, value) => {
+                base.insert(key, value);
+            }
+        }
+    }
+}
+
+fn compile(setting: &str, pattern: &str) -> Result<Regex, PreformError> {
+    Regex::new(pattern).map_err(|e| PreformError::Validation {
+        setting: setting.to_string(),
+        message: e.to_string(),
+    })
+}
+
+/// Types listed in the `[types]` section of `.pre-form.ini`, one key per type
+/// (`feat =`, a value is ignored). Other sections are ignored; a missing file
+/// yields no types, an unreadable one a warning.
+pub fn ini_types() -> Vec<String> {
+    let raw = match fs::read_to_string(".pre-form.ini") {
+        Ok(raw) => raw,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Vec::new(),
+        Err(e) => {
+            eprintln!("pre-form: cannot read .pre-form.ini: {e}");
+            return Vec::new();
+        }
+    };
+    let ini = match ini::Ini::load_from_str(&raw) {
+        Ok(ini) => ini,
+        Err(e) => {
+            eprintln!("pre-form: ignoring .pre-form.ini: {e}");
+            return Vec::new();
+        }
+    };
+    ini.section(Some("types"))
+        .map(|types| types.iter().map(|(name, _)| name.to_string()).collect())
+        .unwrap_or_default()
+}
+
+/// Types allowed by `package.json`, for JS/TS repos that keep commit settings
+/// there. Reads `config["pre-form"].types`, falling back to commitlint's
+/// `rules["type-enum"]`. A missing file or key yields no types.
+pub fn package_json_types() -> Vec<String> {
+    let Ok(raw) = fs::read_to_string("package.json") else {
+        return Vec::new();
+    };
+    let json: serde_json::Value = match serde_json::from_str(&raw) {
+        Ok(json) => json,
+        Err(e) => {
+            eprintln!("pre-form: ignoring package.json: {e}");
+            return Vec::new();
+        }
+    };
+    let types = json
+        .pointer("/config/pre-form/types")
+        .or_else(|| json.pointer("/commitlint/rules/type-enum/2"));
+    types
+        .and_then(|t| t.as_array())
+        .map(|t| {
+            t.iter()
+                .filter_map(|v| v.as_str().map(str::to_string))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn include_cycles_stop_at_the_repeated_file() {
+        let dir = std::env::temp_dir().join(format!("pre-form-include-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(
+            dir.join("a.toml"),
+            "config_include = \"b.toml\"\nsummary = false\n",
+        )
+        .unwrap();
+        fs::write(
+            dir.join("b.toml"),
+            "config_include = \"a.toml\"\ncompact = true\nsummary = true\n",
+        )
+        .unwrap();
+        let table = load_table(&dir.join("a.toml"), &mut Vec::new()).unwrap();
+        assert_eq!(table.get("compact"), Some(&toml::Value::Boolean(true)));
+        // the including file still wins
+        assert_eq!(table.get("summary"), Some(&toml::Value::Boolean(false)));
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,186 @@
+use anyhow::{Context, Result, bail};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+
+use crate::{App, TextInput, preform_dir};
+
+/// Snapshot of the form, kept on disk so an interrupted session can be resumed.
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Draft {
+    pub commit_type: String,
+    pub scope: String,
+    pub description: String,
+    pub ticket: String,
+    pub body: String,
+    pub footer: String,
+    pub breaking: bool,
+}
+
+pub fn draft_file() -> PathBuf {
+    preform_dir().join(".draft.toml")
+}
+
+/// Where `pre-form draft save` and Ctrl+S keep named drafts.
+pub fn drafts_dir() -> PathBuf {
+    preform_dir().join("drafts")
+}
+
+/// Draft names become file names, so keep them to `[A-Za-z0-9._-]`.
+pub fn is_valid_draft_name(name: &str) -> bool {
+    !name.is_empty()
+        && !name.starts_with('.')
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '_' | '-'))
+}
+
+/// The names of the saved drafts, sorted.
+pub fn list_named() -> Vec<String> {
+    let Ok(entries) = fs::read_dir(drafts_dir()) else {
+        return Vec::new();
+    };
+    let mut names: Vec<String> = entries
+        .filter_map(|e| {
+            let path = e.ok()?.path();
+            if path.extension()? != "toml" {
+                return None;
+            }
+            Some(path.file_stem()?.to_str()?.to_string())
+        })
+        .collect();
+    names.sort();
+    names
+}
+
+fn named_file(name: &str) -> Result<PathBuf> {
+    if !is_valid_draft_name(name) {
+        bail!("invalid draft name `{name}`; use letters, digits, `.`, `_` and `-`");
+    }
+    Ok(drafts_dir().join(format!("{name}.toml")))
+}
+
+impl Draft {
+    pub fn capture(app: &App) -> Draft {
+        Draft {
+            commit_type: app.types[app.type_idx].name.clone(),
+            scope: app.scope.value.clone(),
+            description: app.description.value.clone(),
+            ticket: app.ticket.value.clone(),
+            body: app.body.value.clone(),
+            footer: app.footer_text(),
+            breaking: app.breaking,
+        }
+    }
+
+    pub fn apply(self, app: &mut App) {
+        if let Some(idx) = app.types.iter().position(|t| t.name == self.commit_type) {
+            app.type_idx = idx;
+        }
+        app.scope = TextInput::from(self.scope);
+        app.description = TextInput::from(self.description);
+        app.ticket = TextInput::from(self.ticket);
+        app.body = TextInput::from(self.body);
+        app.set_footer(&self.footer);
+        app.breaking = self.breaking;
+    }
+
+    /// The draft left behind by an earlier session, if any.
+    pub fn load() -> Option<Draft> {
+        let raw = fs::read_to_string(draft_file()).ok()?;
+        toml::from_str(&raw).ok()
+    }
+
+    pub fn save(&self) -> Result<()> {
+        fs::create_dir_all(preform_dir()).context("create .pre-form-git failed")?;
+        let raw = toml::to_string(self).context("serializing draft failed")?;
+        fs::write(draft_file(), raw).context("writing draft failed")
+    }
+
+    /// A draft saved under `name`.
+    pub fn load_named(name: &str) -> Result<Draft> {
+        let path = named_file(name)?;
+        let raw =
+            fs::read_to_string(&path).with_context(|| format!("no saved draft named `{name}`"))?;
+        toml::from_str(&raw).with_context(|| format!("reading {} failed", path.display()))
+    }
+
+    /// Keep the draft under `name`, replacing any draft of that name.
+    pub fn save_named(&self, name: &str) -> Result<()> {
+        let path = named_file(name)?;
+        fs::create_dir_all(drafts_dir()).context("create drafts directory failed")?;
+        let raw = toml::to_string(self).context("serializing draft failed")?;
+        fs::write(&path, raw).with_context(|| format!("writing {} failed", path.display()))
+    }
+
+    pub fn discard() -> Result<()> {
+        match fs::remove_file(draft_file()) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => {
+                Err(e).context("removing draft failed")
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Debounces draft writes so typing doesn't hit the disk on every keystroke.
+pub struct Autosave {
+    saved: Draft,
+    /// When the first and the last unsaved edit were made.
+    pending: Option<(Instant, Instant)>,
+    /// Keys pressed since the last write.
+    edits: u32,
+}
+
+impl Autosave {
+    pub fn new(app: &App) -> Autosave {
+        Autosave {
+            saved: Draft::capture(app),
+            pending: None,
+            edits: 0,
+        }
+    }
+
+    pub fn touch(&mut self) {
+        let now = Instant::now();
+        self.pending = Some(match self.pending {
+            Some((first, _)) => (first, now),
+            None => (now, now),
+        });
+        self.edits += 1;
+    }
+
+    /// Write the draft once edits have settled for `autosave_idle_ms`, or
+    /// after `autosave_edits` keys of steady typing, if something changed.
+    pub fn tick(&mut self, app: &App) -> Result<()> {
+        let Some((_, last)) = self.pending else {
+            return Ok(());
+        };
+        let idle = last.elapsed() >= Duration::from_millis(app.config.autosave_idle_ms);
+        let busy = app
+            .config
+            .autosave_edits
+            .is_some_and(|edits| self.edits >= edits);
+        if idle || busy {
+            self.flush(app)?;
+        }
+        Ok(())
+    }
+
+    /// Write any unsaved edits now, as when the form is left without writing
+    /// the message.
+    pub fn flush(&mut self, app: &App) -> Result<()> {
+        if self.pending.take().is_none() {
+            return Ok(());
+        }
+        self.edits = 0;
+        let draft = Draft::capture(app);
+        if draft != self.saved {
+            draft.save()?;
+            self.saved = draft;
+        }
+        Ok(())
+    }
+}
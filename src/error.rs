@@ -0,0 +1,43 @@
+use std::io;
+use std::path::PathBuf;
+
+/// Everything the library can fail with. The binary turns these into `anyhow`
+/// errors at the CLI boundary.
+#[derive(Debug, thiserror::Error)]
+pub enum PreformError {
+    /// `config.toml` isn't valid TOML or has keys of the wrong type.
+    #[error("failed to parse `{path}`: {source}")]
+    ConfigParse {
+        path: PathBuf,
+        #[source]
+        source: toml::de::Error,
+    },
+    /// Not run inside a Git working tree.
+    #[error("not inside a git repository")]
+    NotAGitRepo,
+    /// Writing a hook script failed.
+    #[error("failed to install the {hook} hook at `{path}`")]
+    HookInstall {
+        hook: String,
+        path: PathBuf,
+        #[source]
+        source: io::Error,
+    },
+    /// Reading or writing one of pre-form's files failed.
+    #[error("failed to access `{path}`")]
+    Io {
+        path: PathBuf,
+        #[source]
+        source: io::Error,
+    },
+    /// A setting parsed but its value is unusable, e.g. an invalid regex.
+    #[error("invalid {setting}: {message}")]
+    Validation { setting: String, message: String },
+}
+
+impl PreformError {
+    pub(crate) fn io(path: impl Into<PathBuf>) -> impl FnOnce(io::Error) -> PreformError {
+        let path = path.into();
+        move |source| PreformError::Io { path, source }
+    }
+}
@@ -0,0 +1,190 @@
+use std::collections::BTreeMap;
+use std::fmt;
+
+use crate::message::is_trailer;
+
+/// One entry of the footer: a git trailer, or free text for anything else.
+#[derive(Debug, Clone, PartialEq)]
+pub enum FooterItem {
+    /// `Refs: #12`, `Fixes #3`, `BREAKING CHANGE: ...`. The value may span
+    /// several lines, continued with leading whitespace.
+    Trailer {
+        token: String,
+        /// `": "`, or `" "` for the `Fixes #3` form.
+        separator: &'static str,
+        value: String,
+    },
+    Text(String),
+}
+
+impl FooterItem {
+    fn parse_line(line: &str) -> FooterItem {
+        if !is_trailer(line) {
+            return FooterItem::Text(line.to_string());
+        }
+        // `BREAKING CHANGE` is the one token with a space in it
+        let token_end = if line.starts_with("BREAKING CHANGE: ") {
+            "BREAKING CHANGE".len()
+        } else {
+            line.find([':', ' ']).unwrap_or(line.len())
+        };
+        let (token, rest) = line.split_at(token_end);
+        let (separator, value) = match rest.strip_prefix(": ") {
+            Some(value) => (": ", value),
+            None => (" ", &rest[1..]),
+        };
+        FooterItem::Trailer {
+            token: token.to_string(),
+            separator,
+            value: value.to_string(),
+        }
+    }
+}
+
+impl fmt::Display for FooterItem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FooterItem::Trailer {
+                token,
+                separator,
+                value,
+            } => write!(f, "{token}{separator}{value}"),
+            FooterItem::Text(text) => f.write_str(text),
+        }
+    }
+}
+
+/// Split a footer into items, one per trailer. Indented lines continue the
+/// item before them; other lines become free text items.
+pub fn parse_items(footer: &str) -> Vec<FooterItem> {
+    let mut items: Vec<FooterItem> = Vec::new();
+    for line in footer.lines() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let continues = line.starts_with([' ', '\t']);
+        match items.last_mut() {
+            Some(FooterItem::Trailer { value, .. } | FooterItem::Text(value)) if continues => {
+                value.push('\n');
+                value.push_str(line);
+            }
+            _ => items.push(FooterItem::parse_line(line)),
+        }
+    }
+    items
+}
+
+/// The footer text for `items`, in order.
+pub fn join_items(items: &[FooterItem]) -> String {
+    items
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// `footer` with a `Branch: <branch>` trailer appended, unless there is no
+/// branch (detached HEAD) or the footer already names one.
+pub fn with_branch_trailer(footer: &str, branch: Option<&str>) -> String {
+    let Some(branch) = branch else {
+        return footer.to_string();
+    };
+    with_trailers(footer, &[trailer("Branch", branch)])
+}
+
+/// `footer` with `trailers` appended, except those whose token it already has.
+pub fn with_trailers(footer: &str, trailers: &[FooterItem]) -> String {
+    let mut items = parse_items(footer);
+    for new in trailers {
+        let FooterItem::Trailer {
+            token: new_token, ..
+        } = new
+        else {
+            continue;
+        };
+        let named = items
+            .iter()
+            .any(|item| matches!(item, FooterItem::Trailer { token, .. } if token == new_token));
+        if !named {
+            items.push(new.clone());
+        }
+    }
+    join_items(&items)
+}
+
+/// A `token: value` trailer for each `env_trailers` entry (variable name to
+/// token) whose variable `lookup` finds set and non-empty.
+pub fn env_trailers(
+    vars: &BTreeMap<String, String>,
+    lookup: impl Fn(&str) -> Option<String>,
+) -> Vec<FooterItem> {
+    vars.iter()
+        .filter_map(|(var, token)| {
+            let value = lookup(var)?;
+            let value = value.trim();
+            (!value.is_empty()).then(|| trailer(token, value))
+        })
+        .collect()
+}
+
+fn trailer(token: &str, value: &str) -> FooterItem {
+    FooterItem::Trailer {
+        token: token.to_string(),
+        separator: ": ",
+        value: value.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn branch_trailer_goes_last() {
+        assert_eq!(
+            with_branch_trailer("Refs: #12", Some("feature/login")),
+            "Refs: #12\nBranch: feature/login"
+        );
+        assert_eq!(with_branch_trailer("", Some("main")), "Branch: main");
+    }
+
+    #[test]
+    fn detached_head_adds_nothing() {
+        assert_eq!(with_branch_trailer("Refs: #12", None), "Refs: #12");
+    }
+
+    #[test]
+    fn existing_branch_trailer_is_kept() {
+        let footer = "Branch: old-name\nRefs: #12";
+        assert_eq!(with_branch_trailer(footer, Some("main")), footer);
+    }
+
+    fn vars() -> BTreeMap<String, String> {
+        BTreeMap::from([
+            ("CI_CHANGE_ID".to_string(), "Change-Id".to_string()),
+            ("CI_REVIEW_URL".to_string(), "Reviewed-on".to_string()),
+            ("CI_TICKET".to_string(), "Refs".to_string()),
+        ])
+    }
+
+    #[test]
+    fn set_env_vars_become_trailers() {
+        let env = |var: &str| match var {
+            "CI_CHANGE_ID" => Some("I1234".to_string()),
+            "CI_TICKET" => Some("  ".to_string()),
+            _ => None,
+        };
+        let trailers = env_trailers(&vars(), env);
+        assert_eq!(join_items(&trailers), "Change-Id: I1234");
+    }
+
+    #[test]
+    fn env_trailers_skip_tokens_already_in_the_footer() {
+        let env = |var: &str| Some(format!("from-{var}"));
+        let trailers = env_trailers(&vars(), env);
+        assert_eq!(
+            with_trailers("Refs: #12", &trailers),
+            "Refs: #12\nChange-Id: from-CI_CHANGE_ID\nReviewed-on: from-CI_REVIEW_URL"
+        );
+    }
+}
@@ -0,0 +1,84 @@
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::Span;
+
+/// Case-insensitive subsequence match of `pattern` against `candidate`.
+/// Returns the char indices of `candidate` that matched, or `None`.
+pub fn fuzzy_match(pattern: &str, candidate: &str) -> Option<Vec<usize>> {
+    let mut wanted = pattern.chars().flat_map(char::to_lowercase).peekable();
+    let mut positions = Vec::new();
+    for (i, c) in candidate.chars().enumerate() {
+        let Some(&next) = wanted.peek() else {
+            break;
+        };
+        if c.to_lowercase().eq(std::iter::once(next)) {
+            positions.push(i);
+            wanted.next();
+        }
+    }
+    wanted.peek().is_none().then_some(positions)
+}
+
+/// `text` as spans with the chars at `positions` highlighted.
+pub fn highlight(text: &str, positions: &[usize]) -> Vec<Span<'static>> {
+    let marked = Style::default()
+        .fg(Color::Yellow)
+        .add_modifier(Modifier::UNDERLINED);
+    let mut spans: Vec<Span> = Vec::new();
+    let mut run = String::new();
+    let mut run_marked = false;
+    for (i, c) in text.chars().enumerate() {
+        let is_marked = positions.contains(&i);
+        if is_marked != run_marked && !run.is_empty() {
+            let style = if run_marked { marked } else { Style::default() };
+            spans.push(Span::styled(std::mem::take(&mut run), style));
+        }
+        run_marked = is_marked;
+        run.push(c);
+    }
+    if !run.is_empty() {
+        let style = if run_marked { marked } else { Style::default() };
+        spans.push(Span::styled(run, style));
+    }
+    spans
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn matched_chars_are_reported_by_index() {
+        assert_eq!(fuzzy_match("ft", "feat"), Some(vec![0, 3]));
+        assert_eq!(fuzzy_match("feat", "feat"), Some(vec![0, 1, 2, 3]));
+        // char indices, not byte offsets
+        assert_eq!(fuzzy_match("ui", "✨ ui"), Some(vec![2, 3]));
+    }
+
+    #[test]
+    fn matching_ignores_case() {
+        assert_eq!(fuzzy_match("API", "api-client"), Some(vec![0, 1, 2]));
+        assert_eq!(fuzzy_match("ac", "ApiClient"), Some(vec![0, 3]));
+    }
+
+    #[test]
+    fn out_of_order_or_missing_chars_do_not_match() {
+        assert_eq!(fuzzy_match("tf", "feat"), None);
+        assert_eq!(fuzzy_match("fixx", "fix"), None);
+        assert_eq!(fuzzy_match("a", ""), None);
+    }
+
+    #[test]
+    fn empty_pattern_matches_everything() {
+        assert_eq!(fuzzy_match("", "feat"), Some(Vec::new()));
+        assert_eq!(fuzzy_match("", ""), Some(Vec::new()));
+    }
+
+    #[test]
+    fn highlight_splits_marked_runs() {
+        let spans = highlight("feat", &[0, 3]);
+        let text: Vec<&str> = spans.iter().map(|s| s.content.as_ref()).collect();
+        assert_eq!(text, ["f", "ea", "t"]);
+        assert_ne!(spans[0].style, Style::default());
+        assert_eq!(spans[1].style, Style::default());
+    }
+}
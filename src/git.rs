@@ -0,0 +1,392 @@
+use encoding_rs::{Encoding, UTF_8};
+use std::process::Command;
+
+use crate::template;
+
+/// Run `git` with `args` and return trimmed stdout, or `None` if git is missing
+/// or the command fails.
+pub fn git(args: &[&str]) -> Option<String> {
+    let output = Command::new("git").args(args).output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    Some(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+/// A git config value, if set.
+pub fn config_value(key: &str) -> Option<String> {
+    git(&["config", "--get", key]).filter(|v| !v.is_empty())
+}
+
+/// A boolean git config value; unset or unreadable counts as `false`.
+pub fn config_bool(key: &str) -> bool {
+    git(&["config", "--type=bool", "--get", key]).is_some_and(|v| v == "true")
+}
+
+/// Whether commits will be GPG/SSH signed (`commit.gpgsign`).
+pub fn signing_enabled() -> bool {
+    config_bool("commit.gpgsign")
+}
+
+/// Whether `rev` carries a signature of any kind (good, bad or unverifiable).
+pub fn commit_is_signed(rev: &str) -> bool {
+    git(&["log", "-1", "--format=%G?", rev])
+        .is_some_and(|status| !status.is_empty() && status != "N")
+}
+
+/// `git diff --cached --stat`; `None` outside a repository, empty when nothing
+/// is staged.
+pub fn staged_stat() -> Option<String> {
+    git(&["diff", "--cached", "--stat"])
+}
+
+/// Where a staged change sits.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ChangeLocation {
+    /// A hunk starting at `line`, with the enclosing function or section Git
+    /// found for it (empty when it found none).
+    Hunk {
+        file: String,
+        line: usize,
+        context: String,
+    },
+    Binary {
+        file: String,
+    },
+}
+
+/// The hunks of the staged changes, without their content. `None` outside a
+/// repository.
+pub fn staged_hunks() -> Option<Vec<ChangeLocation>> {
+    // no context lines: only the hunk headers are wanted
+    let diff = git(&["diff", "--cached", "--no-color", "--no-ext-diff", "-U0"])?;
+    Some(parse_hunks(&diff))
+}
+
+/// The hunk headers and binary files of a unified diff.
+pub fn parse_hunks(diff: &str) -> Vec<ChangeLocation> {
+    let mut locations = Vec::new();
+    let mut file = String::new();
+    for line in diff.lines() {
+        if let Some(paths) = line.strip_prefix("diff --git ") {
+            file = paths
+                .rsplit_once(" b/")
+                .map_or(paths, |(_, b)| b)
+                .to_string();
+        } else if let Some(path) = line.strip_prefix("+++ b/") {
+            file = path.to_string();
+        } else if line.starts_with("Binary files ") {
+            locations.push(ChangeLocation::Binary { file: file.clone() });
+        } else if let Some(header) = line.strip_prefix("@@ ")
+            && let Some((ranges, context)) = header.split_once(" @@")
+        {
+            // `-12,3 +14,5`: the new side starts at 14
+            let line = ranges
+                .split(' ')
+                .find_map(|r| r.strip_prefix('+'))
+                .and_then(|r| r.split(',').next())
+                .and_then(|n| n.parse().ok())
+                .unwrap_or(0);
+            locations.push(ChangeLocation::Hunk {
+                file: file.clone(),
+                line,
+                context: context.trim().to_string(),
+            });
+        }
+    }
+    locations
+}
+
+/// Bytes to write for `msg`, transcoded to `i18n.commitEncoding` when the repo
+/// uses something other than UTF-8.
+pub fn encode_message(msg: &str) -> Vec<u8> {
+    let Some(label) = config_value("i18n.commitEncoding") else {
+        return msg.as_bytes().to_vec();
+    };
+    match Encoding::for_label(label.as_bytes()) {
+        Some(encoding) if encoding != UTF_8 => {
+            let (bytes, _, lossy) = encoding.encode(msg);
+            if lossy {
+                eprintln!("pre-form: some characters cannot be represented in {label}");
+            }
+            bytes.into_owned()
+        }
+        Some(_) => msg.as_bytes().to_vec(),
+        None => {
+            eprintln!("pre-form: unknown i18n.commitEncoding `{label}`, writing UTF-8");
+            msg.as_bytes().to_vec()
+        }
+    }
+}
+
+/// Paths of staged files, relative to the repository root.
+pub fn staged_files() -> Vec<String> {
+    git(&["diff", "--cached", "--name-only"])
+        .map(|out| out.lines().map(str::to_string).collect())
+        .unwrap_or_default()
+}
+
+/// How many files have staged and how many have unstaged changes (untracked
+/// ones included); `None` outside a repository.
+pub fn change_counts() -> Option<(usize, usize)> {
+    git(&["status", "--porcelain=v2"]).map(|raw| parse_change_counts(&raw))
+}
+
+/// Staged and unstaged counts from `git status --porcelain=v2`. A file with
+/// both kinds of change counts for each.
+pub fn parse_change_counts(raw: &str) -> (usize, usize) {
+    let (mut staged, mut unstaged) = (0, 0);
+    for line in raw.lines() {
+        match line.split_once(' ') {
+            Some(("1" | "2", rest)) => {
+                let mut xy = rest.chars();
+                staged += usize::from(xy.next().is_some_and(|x| x != '.'));
+                unstaged += usize::from(xy.next().is_some_and(|y| y != '.'));
+            }
+            // unmerged and untracked files still need a `git add`
+            Some(("u" | "?", _)) => unstaged += 1,
+            _ => {}
+        }
+    }
+    (staged, unstaged)
+}
+
+/// How many distinct top-level directories `files` live in; files at the
+/// repository root don't count.
+pub fn top_level_dirs(files: &[String]) -> usize {
+    let mut dirs: Vec<&str> = files
+        .iter()
+        .filter_map(|f| f.split_once('/').map(|(dir, _)| dir))
+        .collect();
+    dirs.sort_unstable();
+    dirs.dedup();
+    dirs.len()
+}
+
+/// The checked-out branch, also before its first commit; `None` when HEAD is
+/// detached.
+pub fn current_branch() -> Option<String> {
+    git(&["symbolic-ref", "--short", "HEAD"]).filter(|b| !b.is_empty())
+}
+
+/// Who a commit made now is by, as `Name <email>`, and when, in `git log`'s
+/// default date format. From `git var GIT_AUTHOR_IDENT`.
+pub fn author_ident() -> Option<(String, String)> {
+    let ident = git(&["var", "GIT_AUTHOR_IDENT"])?;
+    let mut parts = ident.trim().rsplitn(3, ' ');
+    let (zone, secs, who) = (parts.next()?, parts.next()?, parts.next()?);
+    Some((who.to_string(), log_date(secs.parse().ok()?, zone)?))
+}
+
+/// A Unix time in `git log`'s default format at the offset `zone` (`+0200`),
+/// e.g. `Thu Oct 16 12:00:00 2026 +0200`.
+fn log_date(secs: i64, zone: &str) -> Option<String> {
+    let sign = match zone.get(..1)? {
+        "-" => -1,
+        _ => 1,
+    };
+    let hours: i64 = zone.get(1..3)?.parse().ok()?;
+    let minutes: i64 = zone.get(3..5)?.parse().ok()?;
+    let local = secs + sign * (hours * 3_600 + minutes * 60);
+    let days = local.div_euclid(86_400);
+    let time = local.rem_euclid(86_400);
+    let (year, month, day) = template::civil_date(days);
+    const WEEKDAYS: [&str; 7] = ["Thu", "Fri", "Sat", "Sun", "Mon", "Tue", "Wed"];
+    const MONTHS: [&str; 12] = [
+        "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+    ];
+    Some(format!(
+        "{} {} {day} {:02}:{:02}:{:02} {year} {zone}",
+        WEEKDAYS[days.rem_euclid(7) as usize],
+        MONTHS[(month - 1) as usize],
+        time / 3_600,
+        time % 3_600 / 60,
+        time % 60,
+    ))
+}
+
+/// Subject of the last commit on HEAD; `None` before the first commit.
+pub fn last_subject() -> Option<String> {
+    git(&["log", "-1", "--format=%s"]).filter(|s| !s.is_empty())
+}
+
+/// Candidates Git tries, in order, for `core.commentChar = auto`.
+const AUTO_COMMENT_CHARS: &str = "#;@!$%^&|:";
+
+/// What comment lines in `raw` start with: `core.commentChar` (or
+/// `core.commentString`), `#` by default. With `auto`, Git picked a character
+/// no message line starts with and wrote its instructions last, so the last
+/// non-empty line tells which one it was.
+pub fn comment_prefix(raw: &str) -> String {
+    let configured =
+        config_value("core.commentString").or_else(|| config_value("core.commentChar"));
+    resolve_comment_prefix(configured.as_deref(), raw)
+}
+
+/// [`comment_prefix`] for a `configured` value read already.
+fn resolve_comment_prefix(configured: Option<&str>, raw: &str) -> String {
+    match configured {
+        Some("auto") => detect_comment_char(raw).to_string(),
+        Some(prefix) => prefix.to_string(),
+        None => "#".to_string(),
+    }
+}
+
+fn detect_comment_char(raw: &str) -> char {
+    raw.lines()
+        .rev()
+        .find(|line| !line.trim().is_empty())
+        .and_then(|line| line.chars().next())
+        .filter(|c| AUTO_COMMENT_CHARS.contains(*c))
+        .unwrap_or('#')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hunk(file: &str, line: usize, context: &str) -> ChangeLocation {
+        ChangeLocation::Hunk {
+            file: file.to_string(),
+            line,
+            context: context.to_string(),
+        }
+    }
+
+    #[test]
+    fn every_hunk_of_a_file_is_listed() {
+        let diff = concat!(
+            "diff --git a/src/lib.rs b/src/lib.rs\n",
+            "index 1111111..2222222 100644\n",
+            "--- a/src/lib.rs\n",
+            "+++ b/src/lib.rs\n",
+            "@@ -12,0 +13,2 @@ fn parse(input: &str) {\n",
+            "+    let x = 1;\n",
+            "+    let y = 2;\n",
+            "@@ -40 +42 @@\n",
+            "-old\n",
+            "+new\n",
+            "diff --git a/README.md b/README.md\n",
+            "--- a/README.md\n",
+            "+++ b/README.md\n",
+            "@@ -3,2 +3 @@ ## Install\n",
+            "-a\n",
+        );
+        assert_eq!(
+            parse_hunks(diff),
+            vec![
+                hunk("src/lib.rs", 13, "fn parse(input: &str) {"),
+                hunk("src/lib.rs", 42, ""),
+                hunk("README.md", 3, "## Install"),
+            ]
+        );
+    }
+
+    #[test]
+    fn binary_files_have_no_hunks() {
+        let diff = concat!(
+            "diff --git a/logo.png b/logo.png\n",
+            "index 1111111..2222222 100644\n",
+            "Binary files a/logo.png and b/logo.png differ\n",
+        );
+        assert_eq!(
+            parse_hunks(diff),
+            vec![ChangeLocation::Binary {
+                file: "logo.png".to_string()
+            }]
+        );
+    }
+
+    #[test]
+    fn new_and_deleted_files_are_named_from_the_diff_header() {
+        let diff = concat!(
+            "diff --git a/src/new.rs b/src/new.rs\n",
+            "new file mode 100644\n",
+            "--- /dev/null\n",
+            "+++ b/src/new.rs\n",
+            "@@ -0,0 +1,3 @@\n",
+            "+fn new() {}\n",
+            "diff --git a/src/old.rs b/src/old.rs\n",
+            "deleted file mode 100644\n",
+            "--- a/src/old.rs\n",
+            "+++ /dev/null\n",
+            "@@ -1,3 +0,0 @@ mod old\n",
+            "-fn old() {}\n",
+        );
+        assert_eq!(
+            parse_hunks(diff),
+            vec![hunk("src/new.rs", 1, ""), hunk("src/old.rs", 0, "mod old")]
+        );
+    }
+
+    #[test]
+    fn comment_prefix_defaults_to_hash() {
+        assert_eq!(resolve_comment_prefix(None, "feat: x\n# Please enter"), "#");
+        assert_eq!(resolve_comment_prefix(None, ""), "#");
+    }
+
+    #[test]
+    fn configured_comment_char_is_used_as_is() {
+        let raw = "feat: x\n; Please enter the commit message";
+        assert_eq!(resolve_comment_prefix(Some(";"), raw), ";");
+        assert_eq!(resolve_comment_prefix(Some("//"), raw), "//");
+    }
+
+    #[test]
+    fn auto_comment_char_is_read_from_the_instructions() {
+        // `#` starts a message line, so Git picked `;` for its instructions
+        let raw = "feat: x\n\n# Heading in the body\n\n; Please enter the commit message\n;\n";
+        assert_eq!(resolve_comment_prefix(Some("auto"), raw), ";");
+        let raw = "feat: x\n\n# Please enter the commit message\n\n";
+        assert_eq!(resolve_comment_prefix(Some("auto"), raw), "#");
+        // a last line Git wouldn't have commented out
+        assert_eq!(resolve_comment_prefix(Some("auto"), "feat: x\nbody"), "#");
+    }
+
+    #[test]
+    fn log_dates_are_shifted_to_their_zone() {
+        assert_eq!(
+            log_date(1_791_720_000, "+0200").as_deref(),
+            Some("Sun Oct 11 14:00:00 2026 +0200")
+        );
+        assert_eq!(
+            log_date(1_791_720_000, "-0530").as_deref(),
+            Some("Sun Oct 11 06:30:00 2026 -0530")
+        );
+        // back across the epoch and forward into a leap day's next month
+        assert_eq!(
+            log_date(0, "-0100").as_deref(),
+            Some("Wed Dec 31 23:00:00 1969 -0100")
+        );
+        assert_eq!(
+            log_date(1_709_247_600, "+0130").as_deref(),
+            Some("Fri Mar 1 00:30:00 2024 +0130")
+        );
+    }
+
+    #[test]
+    fn malformed_zones_give_no_date() {
+        assert_eq!(log_date(0, ""), None);
+        assert_eq!(log_date(0, "+02"), None);
+        assert_eq!(log_date(0, "+0x00"), None);
+        assert_eq!(log_date(0, "CEST"), None);
+    }
+
+    #[test]
+    fn porcelain_lines_are_counted_by_side() {
+        let raw = concat!(
+            "1 M. N... 100644 100644 100644 a a src/staged.rs\n",
+            "1 .M N... 100644 100644 100644 a a src/edited.rs\n",
+            "1 MM N... 100644 100644 100644 a a src/both.rs\n",
+            "2 R. N... 100644 100644 100644 a a R100 new.rs\told.rs\n",
+            "u UU N... 100644 100644 100644 100644 a a a conflict.rs\n",
+            "? notes.txt\n",
+        );
+        assert_eq!(parse_change_counts(raw), (3, 4));
+    }
+
+    #[test]
+    fn clean_tree_counts_nothing() {
+        assert_eq!(parse_change_counts(""), (0, 0));
+    }
+}
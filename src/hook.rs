@@ -0,0 +1,83 @@
+use std::fs::{self, File};
+use std::io::{self, Write};
+use std::os::unix::fs::PermissionsExt;
+use std::path::PathBuf;
+
+use crate::{PreformError, git};
+
+pub const PREPARE_COMMIT_MSG_HOOK: &str = r#"#!/bin/sh
+# pre-form Git hook: generates commit message via TUI
+pre-form "$@"
+"#;
+
+/// Catches messages the TUI never saw, such as `git commit -m`.
+pub const COMMIT_MSG_HOOK: &str = r#"#!/bin/sh
+# pre-form Git hook: rejects messages that break the configured rules
+pre-form validate "$1"
+"#;
+
+/// What `check_hook` found at a hook's path.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum HookStatus {
+    /// pre-form's script, executable.
+    Installed,
+    Missing,
+    /// pre-form's script, but Git won't run it.
+    NotExecutable,
+    /// Some other script.
+    Modified,
+}
+
+/// The directory Git runs hooks from. Asking Git rather than assuming
+/// `.git/hooks` covers worktrees and submodules, where `.git` is a file
+/// pointing elsewhere, and `core.hooksPath`.
+fn hooks_dir() -> Result<PathBuf, PreformError> {
+    git::git(&["rev-parse", "--git-path", "hooks"])
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .ok_or(PreformError::NotAGitRepo)
+}
+
+fn hook_path(name: &str) -> Result<PathBuf, PreformError> {
+    Ok(hooks_dir()?.join(name))
+}
+
+/// Whether hook `name` is installed as `script`, and its path.
+pub fn check_hook(name: &str, script: &str) -> Result<(HookStatus, PathBuf), PreformError> {
+    let path = hook_path(name)?;
+    let installed = match fs::read_to_string(&path) {
+        Ok(installed) => installed,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok((HookStatus::Missing, path)),
+        Err(source) => return Err(PreformError::Io { path, source }),
+    };
+    let mode = fs::metadata(&path)
+        .map_err(PreformError::io(&path))?
+        .permissions()
+        .mode();
+    let status = if installed != script {
+        HookStatus::Modified
+    } else if mode & 0o111 == 0 {
+        HookStatus::NotExecutable
+    } else {
+        HookStatus::Installed
+    };
+    Ok((status, path))
+}
+
+/// Write `script` as the executable hook `name` and return its path.
+pub fn install_hook(name: &str, script: &str) -> Result<PathBuf, PreformError> {
+    let hook_dir = hooks_dir()?;
+    let hook_path = hook_dir.join(name);
+    let write = || -> io::Result<()> {
+        fs::create_dir_all(&hook_dir)?;
+        let mut file = File::create(&hook_path)?;
+        file.write_all(script.as_bytes())?;
+        fs::set_permissions(&hook_path, fs::Permissions::from_mode(0o755))
+    };
+    write().map_err(|source| PreformError::HookInstall {
+        hook: name.to_string(),
+        path: hook_path.clone(),
+        source,
+    })?;
+    Ok(hook_path)
+}
@@ -0,0 +1,178 @@
+use std::fmt;
+
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use serde::Deserialize;
+
+/// A key binding written like `alt+r`, `ctrl+y`, `f2` or `enter`.
+#[derive(Clone, Copy, Debug, PartialEq, Deserialize)]
+#[serde(try_from = "String")]
+pub struct KeySpec {
+    pub modifiers: KeyModifiers,
+    pub code: KeyCode,
+}
+
+impl KeySpec {
+    pub fn parse(spec: &str) -> Result<KeySpec, String> {
+        let mut modifiers = KeyModifiers::NONE;
+        let mut code = None;
+        for part in spec.split('+').map(str::trim) {
+            let lower = part.to_lowercase();
+            match lower.as_str() {
+                "ctrl" | "control" => modifiers |= KeyModifiers::CONTROL,
+                "alt" | "meta" => modifiers |= KeyModifiers::ALT,
+                "shift" => modifiers |= KeyModifiers::SHIFT,
+                _ if code.is_some() => return Err(format!("`{spec}` names more than one key")),
+                "enter" | "return" => code = Some(KeyCode::Enter),
+                "tab" => code = Some(KeyCode::Tab),
+                "esc" | "escape" => code = Some(KeyCode::Esc),
+                "space" => code = Some(KeyCode::Char(' ')),
+                "up" => code = Some(KeyCode::Up),
+                "down" => code = Some(KeyCode::Down),
+                "left" => code = Some(KeyCode::Left),
+                "right" => code = Some(KeyCode::Right),
+                _ if lower.len() > 1 && lower.starts_with('f') => {
+                    let n = lower[1..]
+                        .parse()
+                        .map_err(|_| format!("unknown key `{part}` in `{spec}`"))?;
+                    code = Some(KeyCode::F(n));
+                }
+                _ => {
+                    let mut chars = lower.chars();
+                    match (chars.next(), chars.next()) {
+                        (Some(c), None) => code = Some(KeyCode::Char(c)),
+                        _ => return Err(format!("unknown key `{part}` in `{spec}`")),
+                    }
+                }
+            }
+        }
+        let code = code.ok_or_else(|| format!("`{spec}` has no key"))?;
+        Ok(KeySpec { modifiers, code })
+    }
+
+    pub fn matches(&self, key: &KeyEvent) -> bool {
+        let relevant = KeyModifiers::CONTROL | KeyModifiers::ALT;
+        if key.modifiers & relevant != self.modifiers & relevant {
+            return false;
+        }
+        let want_shift = self.modifiers.contains(KeyModifiers::SHIFT);
+        let shift = key.modifiers.contains(KeyModifiers::SHIFT);
+        match (self.code, key.code) {
+            // terminals disagree on whether Shift is reported for shifted
+            // characters, so for letters the case tells
+            (KeyCode::Char(want), KeyCode::Char(got)) if got.is_alphabetic() => {
+                got.to_lowercase().eq(want.to_lowercase()) && got.is_uppercase() == want_shift
+            }
+            // `?` or `!` may come with Shift; only `shift+1` insists on it
+            (KeyCode::Char(want), KeyCode::Char(got)) => got == want && (shift || !want_shift),
+            (KeyCode::Tab, KeyCode::BackTab) => want_shift,
+            (want, got) => got == want && shift == want_shift,
+        }
+    }
+}
+
+/// Written the way hints and help show keys: `Ctrl+Y`, `Alt+B`, `F2`.
+impl fmt::Display for KeySpec {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (modifier, name) in [
+            (KeyModifiers::CONTROL, "Ctrl+"),
+            (KeyModifiers::ALT, "Alt+"),
+            (KeyModifiers::SHIFT, "Shift+"),
+        ] {
+            if self.modifiers.contains(modifier) {
+                f.write_str(name)?;
+            }
+        }
+        match self.code {
+            KeyCode::Char(' ') => f.write_str("Space"),
+            KeyCode::Char(c) => write!(f, "{}", c.to_ascii_uppercase()),
+            KeyCode::F(n) => write!(f, "F{n}"),
+            other => write!(f, "{other:?}"),
+        }
+    }
+}
+
+impl TryFrom<String> for KeySpec {
+    type Error = String;
+
+    fn try_from(spec: String) -> Result<Self, Self::Error> {
+        KeySpec::parse(&spec)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(code: KeyCode, modifiers: KeyModifiers) -> KeyEvent {
+        KeyEvent::new(code, modifiers)
+    }
+
+    fn spec(spec: &str) -> KeySpec {
+        KeySpec::parse(spec).unwrap()
+    }
+
+    #[test]
+    fn specs_parse_modifiers_and_keys() {
+        assert_eq!(
+            spec("Ctrl+Shift+X"),
+            KeySpec {
+                modifiers: KeyModifiers::CONTROL | KeyModifiers::SHIFT,
+                code: KeyCode::Char('x'),
+            }
+        );
+        assert_eq!(spec("f2").code, KeyCode::F(2));
+        assert_eq!(spec("alt + enter").modifiers, KeyModifiers::ALT);
+        assert!(KeySpec::parse("ctrl+a+b").is_err());
+        assert!(KeySpec::parse("ctrl").is_err());
+        assert!(KeySpec::parse("hyper+a").is_err());
+    }
+
+    #[test]
+    fn shift_letter_does_not_fire_on_the_plain_letter() {
+        let shift_x = spec("shift+x");
+        assert!(!shift_x.matches(&key(KeyCode::Char('x'), KeyModifiers::NONE)));
+        assert!(shift_x.matches(&key(KeyCode::Char('X'), KeyModifiers::SHIFT)));
+        // Shift left unreported
+        assert!(shift_x.matches(&key(KeyCode::Char('X'), KeyModifiers::NONE)));
+        let x = spec("x");
+        assert!(x.matches(&key(KeyCode::Char('x'), KeyModifiers::NONE)));
+        assert!(!x.matches(&key(KeyCode::Char('X'), KeyModifiers::SHIFT)));
+    }
+
+    #[test]
+    fn ctrl_and_alt_must_match_exactly() {
+        let ctrl_y = spec("ctrl+y");
+        assert!(ctrl_y.matches(&key(KeyCode::Char('y'), KeyModifiers::CONTROL)));
+        assert!(!ctrl_y.matches(&key(KeyCode::Char('y'), KeyModifiers::NONE)));
+        assert!(!ctrl_y.matches(&key(
+            KeyCode::Char('y'),
+            KeyModifiers::CONTROL | KeyModifiers::ALT
+        )));
+        assert!(!ctrl_y.matches(&key(
+            KeyCode::Char('Y'),
+            KeyModifiers::CONTROL | KeyModifiers::SHIFT
+        )));
+    }
+
+    #[test]
+    fn shifted_symbols_and_special_keys() {
+        // `?` is typed with Shift on most layouts
+        let question = spec("?");
+        assert!(question.matches(&key(KeyCode::Char('?'), KeyModifiers::SHIFT)));
+        assert!(question.matches(&key(KeyCode::Char('?'), KeyModifiers::NONE)));
+        assert!(!spec("shift+1").matches(&key(KeyCode::Char('1'), KeyModifiers::NONE)));
+        let f2 = spec("f2");
+        assert!(f2.matches(&key(KeyCode::F(2), KeyModifiers::NONE)));
+        assert!(!f2.matches(&key(KeyCode::F(2), KeyModifiers::SHIFT)));
+        assert!(spec("shift+tab").matches(&key(KeyCode::BackTab, KeyModifiers::SHIFT)));
+        assert!(!spec("tab").matches(&key(KeyCode::BackTab, KeyModifiers::SHIFT)));
+    }
+
+    #[test]
+    fn keys_display_like_hints() {
+        assert_eq!(spec("ctrl+y").to_string(), "Ctrl+Y");
+        assert_eq!(spec("alt+shift+b").to_string(), "Alt+Shift+B");
+        assert_eq!(spec("space").to_string(), "Space");
+        assert_eq!(spec("f10").to_string(), "F10");
+    }
+}
@@ -0,0 +1,36 @@
+//! The non-interactive parts of pre-form: configuration, message parsing and
+//! formatting, and the files kept in `.pre-form-git`. The TUI lives in the
+//! binary.
+
+use std::path::PathBuf;
+
+pub mod config;
+pub mod error;
+pub mod footer;
+pub mod git;
+pub mod hook;
+pub mod keys;
+pub mod message;
+pub mod scopes;
+pub mod template;
+pub mod text;
+
+pub use error::PreformError;
+pub use message::{FormatOptions, MessageFields, build_message};
+
+/// A field of the form, in Tab order.
+#[derive(Clone, Copy, Debug, PartialEq, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Focus {
+    Type,
+    Scope,
+    Description,
+    Ticket,
+    Body,
+    Footer,
+}
+
+/// Where pre-form keeps its per-repo files, relative to the repository root.
+pub fn preform_dir() -> PathBuf {
+    PathBuf::from(".pre-form-git")
+}
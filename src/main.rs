@@ -1,22 +1,52 @@
-use anyhow::{Context, Result};
+use anyhow::{Context, Result, bail};
 use clap::Parser;
-use crossterm::event::{self, DisableMouseCapture, EnableMouseCapture, Event, KeyCode};
+use crossterm::cursor::SetCursorStyle;
+use crossterm::event::{
+    self, DisableMouseCapture, EnableMouseCapture, Event, KeyCode, KeyEvent, KeyModifiers,
+};
 use crossterm::execute;
+use crossterm::style::Print;
 use crossterm::terminal::{
     EnterAlternateScreen, LeaveAlternateScreen, disable_raw_mode, enable_raw_mode,
 };
 use ratatui::backend::CrosstermBackend;
-use ratatui::layout::{Constraint, Direction, Layout, Position};
-use ratatui::style::{Modifier, Style};
-use ratatui::text::Span;
-use ratatui::widgets::{Block, Borders, List, ListItem, ListState, Paragraph};
-use ratatui::{Frame, Terminal};
+use ratatui::layout::{Constraint, Direction, Layout, Margin, Position, Rect};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::{Line, Span, Text};
+use ratatui::widgets::{Block, Borders, Clear, List, ListItem, ListState, Paragraph, Wrap};
+use ratatui::{Frame, Terminal, TerminalOptions, Viewport};
 use std::env;
-use std::fs::{self, File, OpenOptions};
-use std::io::{self, Write};
-use std::os::unix::fs::PermissionsExt;
-use std::path::{Path, PathBuf};
+use std::fs::{self, File};
+use std::io::{self, IsTerminal, Stdout, Write};
+use std::path::PathBuf;
+use std::process::{ExitCode, Stdio};
 use std::time::Duration;
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+
+mod draft;
+mod fuzzy;
+mod markdown;
+mod prompt;
+mod validate;
+
+use pre_form::hook::{
+    COMMIT_MSG_HOOK, HookStatus, PREPARE_COMMIT_MSG_HOOK, check_hook, install_hook,
+};
+use pre_form::{Focus, config, footer, git, keys, message, preform_dir, scopes, template, text};
+
+use config::{
+    Config, CursorShape, FieldLimit, LimitedField, ScopeFormat, Severity, TagPlacement,
+    TicketPlacement,
+};
+use draft::{Autosave, Draft};
+use footer::FooterItem;
+use message::{
+    FormatOptions, HeaderSegment, ParsedMessage, build_message, header_segment,
+    parse_commit_message, parse_header, round_trip_difference, scope_or_placeholder,
+    with_trailing_newline,
+};
+use scopes::{Scope, load_scopes, persist_new_scope, record_scope_use, remove_scope};
+use validate::{Issue, first_error, validate, validate_message};
 
 #[derive(Parser, Debug)]
 #[command(name = "pre-form", version, about, long_about = None)]
@@ -27,22 +57,216 @@ struct Args {
     /// Path passed by Git hook (e.g., .git/COMMIT_EDITMSG)
     #[arg()]
     commit_msg_path: Option<String>,
+
+    /// Source of the message, as passed by Git to prepare-commit-msg
+    #[arg(value_enum)]
+    commit_source: Option<CommitSource>,
+
+    /// Commit SHA, passed by Git when amending or reusing a commit
+    #[arg()]
+    commit_sha: Option<String>,
+
+    /// Render below the current prompt instead of taking over the screen
+    #[arg(long)]
+    inline: bool,
+
+    /// Type the whole header on one line instead of filling in the form
+    #[arg(long)]
+    compact: bool,
+
+    /// Command that Ctrl+P pipes the message to, e.g. `glow -` (overrides
+    /// `preview_cmd` in the config)
+    #[arg(long)]
+    preview_cmd: Option<String>,
+
+    /// Warn when amending a commit that is signed
+    #[arg(long)]
+    amend_safe: bool,
+
+    /// Plain subject and body instead of a Conventional Commits header
+    #[arg(long)]
+    freeform: bool,
+
+    /// Don't print a summary of the message once it's written
+    #[arg(long, short)]
+    quiet: bool,
+
+    /// Start from one of the `[intents]` in the config instead of picking one
+    #[arg(long, env = "PRE_FORM_INTENT")]
+    intent: Option<String>,
+}
+
+/// How a run ended; each outcome maps to its own process exit code.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Outcome {
+    /// The message was written (or deliberately left alone).
+    Written,
+    /// The user quit with Esc / Ctrl+C; the commit should not go ahead.
+    Aborted,
+    /// Non-interactive validation rejected the message.
+    Invalid,
+}
+
+impl From<Outcome> for ExitCode {
+    fn from(outcome: Outcome) -> ExitCode {
+        // 1 is left to errors, which `main` reports through `anyhow`
+        ExitCode::from(match outcome {
+            Outcome::Written => 0,
+            Outcome::Aborted => 2,
+            Outcome::Invalid => 3,
+        })
+    }
+}
+
+/// Where Git says the initial commit message came from.
+#[derive(Clone, Copy, Debug, PartialEq, clap::ValueEnum)]
+enum CommitSource {
+    /// `git commit -m` / `-F`
+    Message,
+    /// `git commit -t` or `commit.template`
+    Template,
+    Merge,
+    Squash,
+    /// `git commit --amend` / `-c` / `-C`
+    Commit,
 }
 
 #[derive(Debug, clap::Subcommand)]
 enum Command {
-    Install,
+    Install {
+        /// Which hook to install: the form on prepare-commit-msg, validation
+        /// on commit-msg, or both
+        #[arg(long, value_enum, conflicts_with = "commit_msg")]
+        hook: Option<HookChoice>,
+        /// Same as `--hook both`
+        #[arg(long)]
+        commit_msg: bool,
+    },
+    /// Verify the hooks are installed as `pre-form install` writes them, e.g.
+    /// in an onboarding script; exits non-zero with how to fix it otherwise
+    CheckHook {
+        /// Which hook to check; pass what `install` was given
+        #[arg(long, value_enum, conflicts_with = "commit_msg")]
+        hook: Option<HookChoice>,
+        /// Same as `--hook both`
+        #[arg(long)]
+        commit_msg: bool,
+    },
+    /// Check a commit message against the configured rules without editing it
+    Validate {
+        /// Message file, e.g. the path Git passes to a commit-msg hook; reads
+        /// stdin when omitted or `-`
+        file: Option<PathBuf>,
+        /// How to report violations
+        #[arg(long, value_enum, default_value_t = ReportFormat::Text)]
+        format: ReportFormat,
+    },
+    /// Keep named drafts to pick up later, e.g. across a branch switch
+    Draft {
+        #[command(subcommand)]
+        action: DraftAction,
+    },
+    /// List or edit the known scopes without opening the form
+    Scopes {
+        #[command(subcommand)]
+        action: ScopesAction,
+    },
+    /// List or edit the commit types without opening the form
+    Types {
+        #[command(subcommand)]
+        action: TypesAction,
+    },
+}
+
+#[derive(clap::Subcommand, Debug)]
+enum DraftAction {
+    /// Save the unfinished message of an interrupted session under a name
+    Save { name: String },
+    /// Offer the named draft the next time the form opens
+    Load { name: String },
+    /// List the saved drafts
+    List,
+}
+
+#[derive(clap::Subcommand, Debug)]
+enum ScopesAction {
+    /// Print the known scopes, most used first
+    List,
+    /// Add a scope to `scopes.txt`, spelled as `scope_case` asks
+    Add {
+        name: String,
+        /// Shown next to the scope in the form's list
+        #[arg(long)]
+        description: Option<String>,
+    },
+    /// Remove a scope from `scopes.txt`; the name must match exactly
+    Remove { name: String },
+}
+
+#[derive(clap::Subcommand, Debug)]
+enum TypesAction {
+    /// Print the types the form offers
+    List,
+    /// Create a type's component file
+    Add { name: String },
+    /// Delete a type's component file; the last one is kept
+    Remove { name: String },
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, clap::ValueEnum)]
+enum HookChoice {
+    /// Open the form for every commit
+    PrepareCommitMsg,
+    /// Only validate messages, however they were written
+    CommitMsg,
+    Both,
 }
 
-#[derive(Clone, Copy, PartialEq)]
-enum Focus {
-    Type,
-    Scope,
-    Description,
-    Body,
-    Footer,
+impl HookChoice {
+    /// `--hook`, or what the older `--commit-msg` flag asked for.
+    fn resolve(hook: Option<HookChoice>, commit_msg: bool) -> HookChoice {
+        match hook {
+            Some(hook) => hook,
+            None if commit_msg => HookChoice::Both,
+            None => HookChoice::PrepareCommitMsg,
+        }
+    }
+
+    /// Hook names with the scripts written for them.
+    fn hooks(self) -> Vec<(&'static str, &'static str)> {
+        let prepare = ("prepare-commit-msg", PREPARE_COMMIT_MSG_HOOK);
+        let validate = ("commit-msg", COMMIT_MSG_HOOK);
+        match self {
+            HookChoice::PrepareCommitMsg => vec![prepare],
+            HookChoice::CommitMsg => vec![validate],
+            HookChoice::Both => vec![prepare, validate],
+        }
+    }
+
+    /// The `pre-form install` invocation that sets these hooks up.
+    fn install_command(self) -> &'static str {
+        match self {
+            HookChoice::PrepareCommitMsg => "pre-form install",
+            HookChoice::CommitMsg => "pre-form install --hook commit-msg",
+            HookChoice::Both => "pre-form install --hook both",
+        }
+    }
 }
 
+#[derive(Clone, Copy, Debug, PartialEq, clap::ValueEnum)]
+enum ReportFormat {
+    /// `file:line:column: message`, one per line on stderr
+    Text,
+    /// A JSON array of `{line, column, message}` on stdout
+    Json,
+}
+
+/// Rows the Type list shows before it scrolls, unless `max_visible_items` says otherwise.
+const TYPE_LIST_ROWS: usize = 5;
+
+/// Where the cursor lands inside an inserted snippet.
+const SNIPPET_CURSOR: &str = "{cursor}";
+
 #[derive(Clone, Default)]
 struct TextInput {
     value: String,
@@ -100,434 +324,3515 @@ impl TextInput {
         let next = self.cursor + self.value[self.cursor..].chars().next().unwrap().len_utf8();
         self.cursor = next;
     }
+    /// Insert `snippet` at the cursor, leaving the cursor on its `{cursor}`
+    /// marker (or at the end of the inserted text).
+    fn insert_snippet(&mut self, snippet: &str) {
+        let (before, after) = snippet.split_once(SNIPPET_CURSOR).unwrap_or((snippet, ""));
+        self.value.insert_str(self.cursor, after);
+        self.value.insert_str(self.cursor, before);
+        self.cursor += before.len();
+    }
     fn move_home(&mut self) {
         self.cursor = 0;
     }
     fn move_end(&mut self) {
         self.cursor = self.value.len();
     }
+    /// Column and row of the cursor, accounting for embedded newlines.
+    fn cursor_offset(&self) -> (u16, u16) {
+        let before = &self.value[..self.cursor];
+        let row = before.matches('\n').count();
+        let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+        let col = before[line_start..].chars().count();
+        (col as u16, row as u16)
+    }
 }
 
 enum OverlayTarget {
     NewType,
     NewScope,
+    /// Edit the description of the selected type.
+    EditTypeDescription,
+    /// Choose from known scopes; the input filters the list.
+    PickScope {
+        selected: usize,
+    },
+    /// Fuzzy-find a type; the input filters the list.
+    FilterTypes {
+        selected: usize,
+    },
+    /// Offer to resume a draft left behind by an interrupted session.
+    RestoreDraft(Draft),
+    /// Name the form's content to keep it as a draft.
+    SaveDraft,
+    /// Ask before Ctrl+N clears a form that has content.
+    ConfirmReset,
+    /// The message as `git log` would list it; read-only.
+    LogPreview {
+        /// `Name <email>` of the author.
+        author: String,
+        date: String,
+        branch: Option<String>,
+    },
+    /// Fuzzy-find across types and scopes at once; the input filters the list.
+    QuickSwitch {
+        selected: usize,
+    },
+    /// Choose one of the configured intents; the input filters the list.
+    PickIntent {
+        selected: usize,
+        names: Vec<String>,
+    },
+    /// Choose a saved draft to load; the input filters the list.
+    PickDraft {
+        selected: usize,
+        names: Vec<String>,
+    },
+    /// The key cheatsheet; read-only, scrolled by `scroll` rows.
+    Help {
+        scroll: usize,
+    },
+}
+
+/// What a key from `GLOBAL_KEYS` does.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Action {
+    Abort,
+    Copy,
+    Preview,
+    QuickSwitch,
+    ReuseLast,
+    LogPreview,
+    AutomationTag,
+    Reset,
+    SaveDraft,
+    LoadDraft,
+    NormalizeScope,
+    ToggleBreaking,
+    ToggleBodyPreview,
+    Help,
 }
+
+/// The keys available from every field. The help overlay is built from this
+/// table, so a binding added here is documented there as well.
+const GLOBAL_KEYS: &[(&str, Action, &str)] = &[
+    ("f1", Action::Help, "show this help"),
+    ("ctrl+c", Action::Abort, "quit without writing"),
+    ("ctrl+y", Action::Copy, "copy the message"),
+    (
+        "ctrl+p",
+        Action::Preview,
+        "open the message in the preview command",
+    ),
+    (
+        "ctrl+g",
+        Action::LogPreview,
+        "show the commit as `git log` would",
+    ),
+    ("ctrl+k", Action::QuickSwitch, "find a type or scope"),
+    (
+        "ctrl+l",
+        Action::ReuseLast,
+        "take type and scope from the last commit",
+    ),
+    (
+        "ctrl+t",
+        Action::AutomationTag,
+        "cycle the automation tags, e.g. [skip ci]",
+    ),
+    ("ctrl+n", Action::Reset, "start over with a blank form"),
+    (
+        "ctrl+s",
+        Action::SaveDraft,
+        "save the form as a named draft",
+    ),
+    ("ctrl+o", Action::LoadDraft, "load a saved draft"),
+    ("alt+b", Action::ToggleBreaking, "mark as a breaking change"),
+    (
+        "alt+n",
+        Action::NormalizeScope,
+        "respell the scope per scope_case",
+    ),
+    (
+        "alt+m",
+        Action::ToggleBodyPreview,
+        "show the body as markdown",
+    ),
+];
+
+fn global_action(key: &KeyEvent) -> Option<Action> {
+    GLOBAL_KEYS
+        .iter()
+        .find(|(spec, ..)| global_key(spec).matches(key))
+        .map(|(_, action, _)| *action)
+}
+
+fn global_key(spec: &str) -> keys::KeySpec {
+    keys::KeySpec::parse(spec).expect("GLOBAL_KEYS are valid key specs")
+}
+
+/// A quick switcher result.
+enum Switch<'a> {
+    /// Index into `App::types`.
+    Type(usize),
+    Scope(&'a Scope),
+}
+
 struct Overlay {
     target: OverlayTarget,
     input: TextInput,
 }
 
+/// A selectable commit type plus the metadata used to display it.
+#[derive(Clone, Debug, PartialEq)]
+struct CommitType {
+    name: String,
+    description: Option<String>,
+    group: Option<String>,
+    require_scope: bool,
+    forbid_body: bool,
+    forbid_footer: bool,
+    emoji: Option<String>,
+    /// Scopes the type is limited to; empty allows any.
+    allowed_scopes: Vec<String>,
+    /// Where changelog tooling files commits of this type.
+    changelog_section: Option<String>,
+}
+
+impl CommitType {
+    /// The description comes from the component file's first line, falling back
+    /// to the config.
+    fn new(name: String, config: &Config) -> Self {
+        let settings = config.types.get(&name);
+        let description = fs::read_to_string(components_dir().join(&name))
+            .ok()
+            .and_then(|s| s.lines().next().map(|l| l.trim().to_string()))
+            .filter(|d| !d.is_empty())
+            .or_else(|| settings.and_then(|t| t.description.clone()));
+        let group = settings.and_then(|t| t.group.clone());
+        let changelog_section = config.changelog_sections.get(&name).cloned();
+        CommitType {
+            name,
+            description,
+            group,
+            require_scope: settings.is_some_and(|t| t.require_scope),
+            forbid_body: settings.is_some_and(|t| t.forbid_body),
+            forbid_footer: settings.is_some_and(|t| t.forbid_footer),
+            emoji: settings.and_then(|t| t.emoji.clone()),
+            allowed_scopes: settings
+                .map(|t| t.allowed_scopes.clone())
+                .unwrap_or_default(),
+            changelog_section,
+        }
+    }
+}
+
+/// Order types so each group is contiguous: ungrouped types first, then groups
+/// in order of first appearance.
+fn group_types(types: &mut [CommitType]) {
+    let mut groups: Vec<Option<String>> = vec![None];
+    for t in types.iter() {
+        if !groups.contains(&t.group) {
+            groups.push(t.group.clone());
+        }
+    }
+    types.sort_by_key(|t| groups.iter().position(|g| *g == t.group));
+}
+
 struct App {
-    types: Vec<String>,
+    types: Vec<CommitType>,
     type_idx: usize,
+    /// Scopes known from `scopes.txt`, offered by the scope picker.
+    scopes: Vec<Scope>,
     scope: TextInput,
     description: TextInput,
+    /// Only shown with a `[ticket]` config; see `TicketConfig`.
+    ticket: TextInput,
     body: TextInput,
+    /// Trailers (and free text) already added to the footer, in order.
+    footer_items: Vec<FooterItem>,
+    /// The footer item with the cursor on it; `None` is the input line.
+    footer_selected: Option<usize>,
+    /// The footer line being typed, added to `footer_items` with Alt+Enter.
     footer: TextInput,
+    /// Marks the header with `!` (a breaking change).
+    breaking: bool,
     focus: Focus,
     overlay: Option<Overlay>,
+    /// The rule that blocked the last confirm, until the next key press.
+    issue: Option<Issue>,
+    /// A non-blocking heads-up for the status line, until the next key press.
+    notice: Option<String>,
+    config: Config,
+    /// Where the finished message gets written (shown in the status line).
+    hook_path: PathBuf,
+    /// `commit.gpgsign` is on, shown as a status indicator.
+    signing: bool,
+    /// Staged change summary for the context panel, read once at startup.
+    staged_stat: Option<String>,
+    /// Files with staged and with unstaged changes, read at startup.
+    change_counts: Option<(usize, usize)>,
+    /// Staged hunk headers for the context panel, read at startup when
+    /// `show_hunks` is on.
+    hunks: Vec<git::ChangeLocation>,
+    /// First hunk row shown; PageUp/PageDown scroll.
+    hunk_scroll: usize,
+    /// The single header input of compact mode; `None` shows the full form.
+    compact: Option<TextInput>,
+    /// Set while Tab cycles through completions in compact mode.
+    completion: Option<Completion>,
+    /// A message that was flagged for not parsing back the same; confirming
+    /// it unchanged writes it anyway.
+    round_trip_warned: Option<String>,
+    /// Set once Enter was refused; from then on every open issue is shown
+    /// under its field until fixed.
+    show_issues: bool,
+    /// Abbreviated hash of the commit being amended, for the banner.
+    amending: Option<String>,
+    /// Show the body rendered as markdown next to it (Alt+M).
+    body_preview: bool,
+    /// The checked-out branch, read at startup with `include_branch_footer`.
+    branch: Option<String>,
+    /// Index into `automation_tags` of the tag Ctrl+T picked, if any.
+    automation_tag: Option<usize>,
+    /// Opened on the first copy; on X11 and Wayland the copied text is only
+    /// served while this is alive.
+    clipboard: Option<arboard::Clipboard>,
+}
+
+/// The types offered for `config`: component files, then types only declared
+/// in the config, package.json or .pre-form.ini, grouped as shown in the list.
+/// Component files with invalid names are returned separately.
+fn load_types(config: &Config) -> (Vec<CommitType>, Vec<String>) {
+    let mut names = Vec::new();
+    let mut skipped = Vec::new();
+    if let Ok(entries) = fs::read_dir(".pre-form-git/components") {
+        for entry in entries.filter_map(Result::ok) {
+            let file_name = entry.file_name();
+            match file_name.to_str() {
+                Some(name) if is_valid_type_name(name) => names.push(name.to_string()),
+                _ => skipped.push(file_name.to_string_lossy().into_owned()),
+            }
+        }
+    }
+    let package_types = config::package_json_types();
+    let ini_types = config::ini_types();
+    for name in config.types.keys().chain(&package_types).chain(&ini_types) {
+        if is_valid_type_name(name) && !names.contains(name) {
+            names.push(name.clone());
+        }
+    }
+    if names.is_empty() {
+        names = vec![
+            "feat".into(),
+            "fix".into(),
+            "docs".into(),
+            "style".into(),
+            "refactor".into(),
+            "test".into(),
+            "chore".into(),
+        ];
+    }
+    let mut types: Vec<CommitType> = names
+        .into_iter()
+        .map(|name| CommitType::new(name, config))
+        .collect();
+    group_types(&mut types);
+    (types, skipped)
 }
 
 impl App {
-    fn new() -> App {
-        let mut types = Vec::new();
-        if let Ok(entries) = fs::read_dir(".pre-form-git/components") {
-            for entry in entries.filter_map(Result::ok) {
-                if let Some(name) = entry.file_name().to_str() {
-                    types.push(name.to_string());
-                }
-            }
-        }
-        if types.is_empty() {
-            types = vec![
-                "feat".into(),
-                "fix".into(),
-                "docs".into(),
-                "style".into(),
-                "refactor".into(),
-                "test".into(),
-                "chore".into(),
-            ];
-        }
-        App {
+    fn new(config: Config, hook_path: PathBuf) -> App {
+        let (types, skipped) = load_types(&config);
+        for name in skipped {
+            eprintln!("pre-form: skipping type `{name}` (type names must match [a-z0-9-]+)");
+        }
+        let mut app = App::offline(config, hook_path, types);
+        let staged = git::staged_files();
+        app.scopes = load_scopes();
+        scopes::merge_detected(&mut app.scopes, &scopes::detect_scopes(&staged));
+        app.signing = git::signing_enabled();
+        app.staged_stat = git::staged_stat();
+        app.change_counts = git::change_counts();
+        if app.config.show_hunks {
+            app.hunks = git::staged_hunks().unwrap_or_default();
+        }
+        if app.config.include_branch_footer {
+            app.branch = git::current_branch();
+        }
+        // the compact header is a conventional one
+        if app.config.compact && !app.config.freeform {
+            app.compact = Some(TextInput::new());
+        }
+        app.apply_body_template();
+        app.apply_footer_template();
+        app.apply_env_trailers();
+        if app.config.ticket.is_some()
+            && let Some(ticket) =
+                git::current_branch().and_then(|b| template::ticket_from_branch(&b))
+        {
+            app.ticket = TextInput::from(ticket);
+        }
+        let spread = git::top_level_dirs(&staged);
+        if app.config.split_hint_dirs > 0 && spread > app.config.split_hint_dirs {
+            app.notice = Some(format!(
+                "staged changes span {spread} top-level directories; \
+                 consider splitting them with `git add -p`"
+            ));
+        }
+        app
+    }
+
+    /// An empty form over `types` that reads nothing from the repository:
+    /// no staged changes, branch, scopes or templates. Enough to check a
+    /// message against the config.
+    fn offline(config: Config, hook_path: PathBuf, types: Vec<CommitType>) -> App {
+        let mut app = App {
             types,
             type_idx: 0,
+            scopes: Vec::new(),
             scope: TextInput::new(),
             description: TextInput::new(),
+            ticket: TextInput::new(),
             body: TextInput::new(),
+            footer_items: Vec::new(),
+            footer_selected: None,
             footer: TextInput::new(),
-            focus: Focus::Type,
+            breaking: false,
+            focus: config.default_focus.unwrap_or(Focus::Type),
             overlay: None,
+            issue: None,
+            notice: None,
+            config,
+            hook_path,
+            signing: false,
+            staged_stat: None,
+            change_counts: None,
+            hunks: Vec::new(),
+            hunk_scroll: 0,
+            compact: None,
+            completion: None,
+            round_trip_warned: None,
+            show_issues: false,
+            amending: None,
+            body_preview: false,
+            branch: None,
+            automation_tag: None,
+            clipboard: None,
+        };
+        if !app.field_enabled(app.focus) {
+            app.focus = app.field_order()[0];
         }
+        app
     }
 
-    fn commit_message(&self) -> String {
-        let scope = &self.scope.value;
-        let description = &self.description.value;
-        let body = &self.body.value;
-        let footer = &self.footer.value;
-
-        let prefix = if scope.is_empty() {
-            format!("{}: {}", self.types[self.type_idx], description)
-        } else {
-            format!("{}({}): {}", self.types[self.type_idx], scope, description)
-        };
-        let mut msg = prefix;
-        if !body.is_empty() {
-            msg.push_str("\n\n");
-            msg.push_str(body);
+    /// Whether `focus` is part of the form for the selected type.
+    fn field_enabled(&self, focus: Focus) -> bool {
+        if self.config.freeform {
+            return matches!(focus, Focus::Description | Focus::Body);
         }
-        if !footer.is_empty() {
-            msg.push_str("\n\n");
-            msg.push_str(footer);
+        let commit_type = &self.types[self.type_idx];
+        match focus {
+            Focus::Body => !commit_type.forbid_body,
+            Focus::Footer => !commit_type.forbid_footer,
+            Focus::Ticket => self.config.ticket.is_some(),
+            _ => true,
         }
-        msg
     }
-}
-
-// ---------- persistence helpers ----------
-fn preform_dir() -> PathBuf {
-    PathBuf::from(".pre-form-git")
-}
-fn components_dir() -> PathBuf {
-    preform_dir().join("components")
-}
-fn scopes_file() -> PathBuf {
-    preform_dir().join("scopes.txt")
-}
 
-fn persist_new_type(name: &str) -> Result<()> {
-    fs::create_dir_all(components_dir()).context("creating components dir failed")?;
-    let p = components_dir().join(name);
-    if !p.exists() {
-        File::create(p).context("creating type file failed")?;
+    /// The fields shown for the selected type, in Tab order.
+    fn field_order(&self) -> Vec<Focus> {
+        [
+            Focus::Type,
+            Focus::Scope,
+            Focus::Description,
+            Focus::Ticket,
+            Focus::Body,
+            Focus::Footer,
+        ]
+        .into_iter()
+        .filter(|focus| self.field_enabled(*focus))
+        .collect()
     }
-    Ok(())
-}
 
-fn persist_new_scope(name: &str) -> Result<()> {
-    fs::create_dir_all(preform_dir()).context("create .pre-form-git failed")?;
-    let mut f = OpenOptions::new()
-        .create(true)
-        .append(true)
-        .open(scopes_file())
-        .context("open scopes.txt failed")?;
-    writeln!(f, "{}", name).context("write scope failed")?;
-    Ok(())
-}
+    /// The next enabled field in Tab order.
+    fn next_focus(&self) -> Focus {
+        let order = self.field_order();
+        let at = order.iter().position(|f| *f == self.focus).unwrap_or(0);
+        order[(at + 1) % order.len()]
+    }
 
-// ---------- UI ----------
-fn draw_ui(f: &mut Frame, app: &App) {
-    let area = f.area();
-    let chunks = Layout::default()
-        .direction(Direction::Vertical)
-        .margin(1)
-        .constraints([
-            Constraint::Length(7), // Type list
-            Constraint::Length(3), // Scope
-            Constraint::Length(3), // Description
-            Constraint::Min(3),    // Body
-            Constraint::Length(3), // Footer
-        ])
-        .split(area);
+    /// Move on to `focus` after finishing a field, with the cursor at the end
+    /// of its text. Tab and Alt+digit keep each field's own cursor instead.
+    fn advance_to(&mut self, focus: Focus) {
+        self.focus = focus;
+        if let Some(input) = current_input_mut(self) {
+            input.move_end();
+        }
+    }
 
-    // Types list (dropdown-like)
-    let items: Vec<ListItem> = app
-        .types
-        .iter()
-        .map(|t| ListItem::new(Span::raw(t)))
+    /// Change the selected type, warning when it drops text already entered.
+    fn select_type(&mut self, idx: usize) {
+        self.type_idx = idx;
+        let commit_type = &self.types[idx];
+        let dropped: Vec<&str> = [
+            (
+                commit_type.forbid_body && !self.body.value.is_empty(),
+                "body",
+            ),
+            (
+                commit_type.forbid_footer && !self.footer_text().is_empty(),
+                "footer",
+            ),
+        ]
+        .into_iter()
+        .filter_map(|(dropped, field)| dropped.then_some(field))
         .collect();
-    let mut state = ListState::default();
-    state.select(Some(app.type_idx));
-    let title_style = if app.focus == Focus::Type {
-        Style::default().add_modifier(Modifier::BOLD)
-    } else {
-        Style::default()
-    };
-    let list = List::new(items)
-        .block(
-            Block::default()
-                .borders(Borders::ALL)
-                .title(Span::styled("Type  ( + to add )", title_style)),
-        )
-        .highlight_symbol("➡ ")
-        .highlight_style(Style::default().add_modifier(Modifier::BOLD));
-    f.render_stateful_widget(list, chunks[0], &mut state);
-
-    // Text inputs
-    let inputs: [(&str, &TextInput, Focus); 4] = [
-        ("Scope  ( + to add )", &app.scope, Focus::Scope),
-        ("Description", &app.description, Focus::Description),
-        ("Body", &app.body, Focus::Body),
-        ("Footer", &app.footer, Focus::Footer),
-    ];
-
-    for (i, (label, ti, focus)) in inputs.iter().enumerate() {
-        let block = Block::default().borders(Borders::ALL).title(Span::styled(
-            *label,
-            if app.focus == *focus {
-                Style::default().add_modifier(Modifier::BOLD)
-            } else {
-                Style::default()
-            },
-        ));
-        let para = Paragraph::new(ti.value.as_str()).block(block);
-        f.render_widget(para, chunks[i + 1]);
-        if app.focus == *focus && app.overlay.is_none() {
-            // cursor inside the block (1 char padding)
-            let x = chunks[i + 1].x + 1 + ti.cursor as u16;
-            let y = chunks[i + 1].y + 1;
-            f.set_cursor_position(Position::new(x, y));
+        if !dropped.is_empty() {
+            self.notice = Some(format!(
+                "`{}` commits have no {}; it will be dropped",
+                commit_type.name,
+                dropped.join(" or ")
+            ));
         }
     }
 
-    // Overlay (modal) to add type/scope
-    if let Some(ov) = &app.overlay {
-        let outer = Layout::default()
-            .direction(Direction::Vertical)
-            .constraints([
-                Constraint::Percentage(40),
-                Constraint::Length(3),
-                Constraint::Percentage(57),
-            ])
-            .split(area);
-        let inner_row = Layout::default()
-            .direction(Direction::Horizontal)
-            .constraints([
-                Constraint::Percentage(20),
-                Constraint::Percentage(60),
-                Constraint::Percentage(20),
-            ])
-            .split(outer[1]);
-
-        let title = match ov.target {
-            OverlayTarget::NewType => "New Type (Enter to save, Esc to cancel)",
-            OverlayTarget::NewScope => "New Scope (Enter to save, Esc to cancel)",
-        };
-        let block = Block::default().borders(Borders::ALL).title(title);
-        let para = Paragraph::new(ov.input.value.as_str()).block(block);
-        f.render_widget(para, inner_row[1]);
-
-        let x = inner_row[1].x + 1 + ov.input.cursor as u16;
-        let y = inner_row[1].y + 1;
-        f.set_cursor_position(Position::new(x, y));
+    /// Start from an existing message. A conventional one fills every field;
+    /// otherwise its first line becomes the description and anything after it
+    /// the body. Git comment lines are dropped.
+    fn prefill_from_raw(&mut self, raw: &str) {
+        let uncommented = uncommented_lines(raw);
+        // only blank lines are dropped; indentation and trailing spaces in the
+        // body are kept as given
+        if let Some(parsed) = parse_commit_message(
+            uncommented.join("\n").trim_matches('\n'),
+            self.config.scope_format,
+        ) && self.apply_parsed(&parsed).is_ok()
+        {
+            return;
+        }
+        let mut lines = uncommented.into_iter();
+        let subject = lines.by_ref().find(|l| !l.trim().is_empty()).unwrap_or("");
+        self.description = TextInput::from(subject.trim().to_string());
+        let rest: Vec<&str> = lines.collect();
+        let body = rest.join("\n").trim().to_string();
+        if !body.is_empty() {
+            self.body = TextInput::from(body);
+        }
     }
-}
 
-// helpers
-fn current_input_mut(app: &mut App) -> Option<&mut TextInput> {
-    match app.focus {
-        Focus::Scope => Some(&mut app.scope),
-        Focus::Description => Some(&mut app.description),
-        Focus::Body => Some(&mut app.body),
-        Focus::Footer => Some(&mut app.footer),
-        _ => None,
+    /// Known scopes fuzzy-matching `filter`, most used first, with the
+    /// positions of the matched characters.
+    fn filtered_scopes(&self, filter: &str) -> Vec<(&Scope, Vec<usize>)> {
+        self.scopes
+            .iter()
+            .filter_map(|s| Some((s, fuzzy::fuzzy_match(filter, &s.name)?)))
+            .collect()
     }
-}
 
-fn maybe_open_overlay(app: &mut App) {
-    app.overlay = match app.focus {
-        Focus::Type => Some(Overlay {
-            target: OverlayTarget::NewType,
-            input: TextInput::new(),
-        }),
-        Focus::Scope => Some(Overlay {
-            target: OverlayTarget::NewScope,
-            input: TextInput::new(),
-        }),
-        _ => None,
-    };
-}
+    /// Indices of the types fuzzy-matching `filter`, with the positions of the
+    /// matched characters.
+    fn filtered_types(&self, filter: &str) -> Vec<(usize, Vec<usize>)> {
+        self.types
+            .iter()
+            .enumerate()
+            .filter_map(|(i, t)| Some((i, fuzzy::fuzzy_match(filter, &t.name)?)))
+            .collect()
+    }
 
-fn run_tui(hook_path: PathBuf) -> Result<()> {
-    enable_raw_mode().context("failed to enable raw mode")?;
+    /// Types, then scopes, fuzzy-matching `filter`, with the positions of the
+    /// matched characters.
+    fn quick_switch(&self, filter: &str) -> Vec<(Switch<'_>, Vec<usize>)> {
+        let types = self
+            .filtered_types(filter)
+            .into_iter()
+            .map(|(i, positions)| (Switch::Type(i), positions));
+        let scopes = self
+            .filtered_scopes(filter)
+            .into_iter()
+            .map(|(s, positions)| (Switch::Scope(s), positions));
+        types.chain(scopes).collect()
+    }
 
-    let mut stdout = io::stdout();
-    execute!(stdout, EnterAlternateScreen, EnableMouseCapture)
-        .context("failed to enter alternate screen / enable mouse capture")?;
+    /// The `names` fuzzy-matching `filter`, with the positions of the matched
+    /// characters.
+    fn filtered_names<'a>(
+        &self,
+        names: &'a [String],
+        filter: &str,
+    ) -> Vec<(&'a String, Vec<usize>)> {
+        names
+            .iter()
+            .filter_map(|n| Some((n, fuzzy::fuzzy_match(filter, n)?)))
+            .collect()
+    }
 
-    let backend = CrosstermBackend::new(stdout);
-    let mut terminal = Terminal::new(backend).context("failed to initialize TUI terminal")?;
+    /// Fill the form from a parsed message. Fails, leaving the form untouched,
+    /// when the type isn't one of the known types.
+    fn apply_parsed(&mut self, parsed: &ParsedMessage) -> Result<(), String> {
+        let idx = self
+            .types
+            .iter()
+            .position(|t| t.name == parsed.commit_type)
+            .ok_or_else(|| format!("unknown type `{}`", parsed.commit_type))?;
+        self.select_type(idx);
+        self.scope = TextInput::from(parsed.scope.clone());
+        self.description = TextInput::from(parsed.description.clone());
+        self.breaking = parsed.breaking;
+        if !parsed.body.is_empty() {
+            self.body = TextInput::from(parsed.body.clone());
+        }
+        if !parsed.footer.is_empty() {
+            self.set_footer(&parsed.footer);
+        }
+        Ok(())
+    }
 
-    let mut app = App::new();
-    loop {
-        terminal
-            .draw(|f| draw_ui(f, &app))
-            .context("failed to draw TUI frame")?;
+    /// Start the body from a commit template (`git commit -t`), without its
+    /// comment lines. An empty template leaves the body as it is.
+    fn prefill_body_from_template(&mut self, raw: &str) {
+        let body = uncommented_lines(raw)
+            .join("\n")
+            .trim_matches('\n')
+            .to_string();
+        if !body.trim().is_empty() {
+            self.body = TextInput::from(body);
+        }
+    }
 
-        if event::poll(Duration::from_millis(200)).context("failed to poll for terminal events")? {
-            if let Event::Key(key) = event::read().context("failed to read terminal event")? {
-                // If an overlay is open, handle it first and continue.
-                if let Some(ov) = &mut app.overlay {
-                    match key.code {
-                        KeyCode::Esc => {
-                            app.overlay = None;
-                        }
-                        KeyCode::Enter => {
-                            let name = ov.input.value.trim();
-                            if !name.is_empty() {
-                                match ov.target {
-                                    OverlayTarget::NewType => {
-                                        persist_new_type(name)?;
-                                        app.types.push(name.to_string());
-                                        app.type_idx = app.types.len() - 1;
-                                    }
-                                    OverlayTarget::NewScope => {
-                                        persist_new_scope(name)?;
-                                        app.scope = TextInput::from(name.to_string());
-                                        app.focus = Focus::Description; // move on
-                                    }
-                                }
-                            }
-                            app.overlay = None;
-                        }
-                        KeyCode::Left => ov.input.move_left(),
-                        KeyCode::Right => ov.input.move_right(),
-                        KeyCode::Home => ov.input.move_home(),
-                        KeyCode::End => ov.input.move_end(),
-                        KeyCode::Delete => ov.input.delete(),
-                        KeyCode::Backspace => ov.input.backspace(),
-                        KeyCode::Char(c) => ov.input.insert_char(c),
-                        _ => {}
-                    }
-                    continue;
-                }
+    /// Whether anything was typed or picked beyond the defaults.
+    fn has_content(&self) -> bool {
+        self.type_idx != 0
+            || self.breaking
+            || [&self.scope, &self.description, &self.ticket, &self.body]
+                .iter()
+                .any(|t| !t.value.is_empty())
+            || !self.footer_text().is_empty()
+    }
 
-                match key.code {
-                    KeyCode::Tab => {
-                        app.focus = match app.focus {
-                            Focus::Type => Focus::Scope,
-                            Focus::Scope => Focus::Description,
-                            Focus::Description => Focus::Body,
-                            Focus::Body => Focus::Footer,
-                            Focus::Footer => Focus::Type,
-                        }
-                    }
-                    KeyCode::Up if app.focus == Focus::Type => {
-                        if app.type_idx > 0 {
-                            app.type_idx -= 1;
-                        }
-                    }
-                    KeyCode::Down if app.focus == Focus::Type => {
-                        if app.type_idx + 1 < app.types.len() {
-                            app.type_idx += 1;
-                        }
-                    }
+    /// Blank every field and go back to the first type and the default focus.
+    fn reset(&mut self) {
+        self.type_idx = 0;
+        self.scope = TextInput::new();
+        self.description = TextInput::new();
+        self.ticket = TextInput::new();
+        self.body = TextInput::new();
+        self.set_footer("");
+        self.breaking = false;
+        self.automation_tag = None;
+        self.round_trip_warned = None;
+        self.show_issues = false;
+        self.focus = self.config.default_focus.unwrap_or(Focus::Type);
+        if !self.field_enabled(self.focus) {
+            self.focus = self.field_order()[0];
+        }
+    }
 
-                    // text editing in inputs
-                    KeyCode::Left => {
-                        if let Some(t) = current_input_mut(&mut app) {
-                            t.move_left();
-                        }
-                    }
-                    KeyCode::Right => {
-                        if let Some(t) = current_input_mut(&mut app) {
-                            t.move_right();
-                        }
-                    }
-                    KeyCode::Home => {
-                        if let Some(t) = current_input_mut(&mut app) {
-                            t.move_home();
-                        }
-                    }
-                    KeyCode::End => {
-                        if let Some(t) = current_input_mut(&mut app) {
-                            t.move_end();
-                        }
-                    }
-                    KeyCode::Delete => {
-                        if let Some(t) = current_input_mut(&mut app) {
-                            t.delete();
-                        }
-                    }
-                    KeyCode::Backspace => {
-                        if let Some(t) = current_input_mut(&mut app) {
-                            t.backspace();
-                        }
-                    }
+    /// Prefill the body from `body_template`, unless it already has content.
+    fn apply_body_template(&mut self) {
+        if !self.body.value.is_empty() {
+            return;
+        }
+        if let Some(template) = &self.config.body_template {
+            self.body = TextInput::from(template.clone());
+        }
+    }
 
-                    // open modal to add type/scope
-                    KeyCode::Char('+') => maybe_open_overlay(&mut app),
+    /// Prefill the footer from `footer_template`, unless it already has content.
+    fn apply_footer_template(&mut self) {
+        if !self.footer_text().is_empty() {
+            return;
+        }
+        if let Some(template) = &self.config.footer_template {
+            let footer = template::expand(template, &template::Variables::from_repo());
+            self.set_footer(&footer);
+        }
+    }
 
-                    KeyCode::Char(c) => match app.focus {
-                        Focus::Scope | Focus::Description | Focus::Body | Focus::Footer => {
-                            if let Some(t) = current_input_mut(&mut app) {
-                                t.insert_char(c);
-                            }
-                        }
-                        _ => {}
-                    },
+    /// Add trailers for the `env_trailers` variables set in the environment,
+    /// such as a `Change-Id` a CI job provides.
+    fn apply_env_trailers(&mut self) {
+        let trailers = footer::env_trailers(&self.config.env_trailers, |var| env::var(var).ok());
+        if !trailers.is_empty() {
+            let footer = footer::with_trailers(&self.footer_text(), &trailers);
+            self.set_footer(&footer);
+        }
+    }
 
-                    // finish
-                    KeyCode::Enter | KeyCode::Esc => break,
-                    _ => {}
-                }
+    /// Move a ticket typed at the start of the description, as
+    /// `description_ticket_pattern` finds it, to the empty Ticket field or
+    /// else a `Refs:` footer line. Left alone when neither is available.
+    fn move_description_ticket(&mut self) {
+        let Some(pattern) = &self.config.description_ticket_regex else {
+            return;
+        };
+        let Some((ticket, rest)) = template::split_leading_ticket(&self.description.value, pattern)
+        else {
+            return;
+        };
+        let to_ticket_field = self.field_enabled(Focus::Ticket)
+            && [ticket.as_str(), ""].contains(&self.ticket.value.trim());
+        if to_ticket_field {
+            self.ticket = TextInput::from(ticket.clone());
+            self.notice = Some(format!("moved {ticket} to the Ticket field"));
+        } else if self.field_enabled(Focus::Footer) {
+            if !self.footer_text().contains(&ticket) {
+                self.footer_items.push(FooterItem::Trailer {
+                    token: "Refs".to_string(),
+                    separator: ": ",
+                    value: ticket.clone(),
+                });
             }
+            self.notice = Some(format!("moved {ticket} to the footer"));
+        } else {
+            return;
         }
+        self.description = TextInput::from(rest);
     }
 
-    // restore terminal state
-    disable_raw_mode().context("failed to disable raw mode")?;
-    execute!(
-        terminal.backend_mut(),
-        LeaveAlternateScreen,
-        DisableMouseCapture
-    )
-    .context("failed to leave alternate screen / disable mouse capture")?;
-    terminal
-        .show_cursor()
-        .context("failed to show terminal cursor")?;
+    /// Take the type and scope of the last commit, for a follow-up in the same
+    /// area. The outcome goes to the status line.
+    fn reuse_last_context(&mut self) {
+        let Some(subject) = git::last_subject() else {
+            self.notice = Some("no earlier commit to take the type from".to_string());
+            return;
+        };
+        let Some(last) = parse_commit_message(&subject, self.config.scope_format) else {
+            self.notice = Some("the last commit isn't a conventional commit".to_string());
+            return;
+        };
+        let Some(idx) = self.types.iter().position(|t| t.name == last.commit_type) else {
+            self.notice = Some(format!(
+                "unknown type `{}` in the last commit",
+                last.commit_type
+            ));
+            return;
+        };
+        self.select_type(idx);
+        self.notice = Some(format!(
+            "took `{}` from the last commit",
+            header_context(&last)
+        ));
+        self.scope = TextInput::from(last.scope);
+    }
 
-    // write out the commit message
-    let msg = app.commit_message();
-    fs::write(&hook_path, msg).with_context(|| {
-        format!(
-            "failed to write commit message to `{}`",
-            hook_path.display()
+    /// Preset the fields the intent named `name` sets. Fails, leaving the form
+    /// untouched, for an unknown intent or type.
+    fn apply_intent(&mut self, name: &str) -> Result<(), String> {
+        let intent = self.config.intents.get(name).cloned().ok_or_else(|| {
+            let known: Vec<&str> = self.config.intents.keys().map(String::as_str).collect();
+            format!("unknown intent `{name}`; configured: {}", known.join(", "))
+        })?;
+        if let Some(commit_type) = &intent.commit_type {
+            let idx = self
+                .types
+                .iter()
+                .position(|t| &t.name == commit_type)
+                .ok_or_else(|| format!("intent `{name}` has unknown type `{commit_type}`"))?;
+            self.select_type(idx);
+        }
+        if let Some(scope) = intent.scope {
+            self.scope = TextInput::from(scope);
+        }
+        if let Some(breaking) = intent.breaking {
+            self.breaking = breaking;
+        }
+        if let Some(body) = intent.body {
+            self.body = TextInput::from(body);
+        }
+        if let Some(footer) = intent.footer {
+            let footer = template::expand(&footer, &template::Variables::from_repo());
+            self.set_footer(&footer);
+        }
+        Ok(())
+    }
+
+    /// Put the composed message on the system clipboard, reporting how it went
+    /// in the status line.
+    fn copy_message(&mut self) {
+        let message = self.commit_message();
+        let copied = match &mut self.clipboard {
+            Some(clipboard) => clipboard.set_text(message),
+            None => arboard::Clipboard::new().and_then(|mut clipboard| {
+                clipboard.set_text(message)?;
+                self.clipboard = Some(clipboard);
+                Ok(())
+            }),
+        };
+        self.notice = Some(match copied {
+            Ok(()) => "copied the message to the clipboard".to_string(),
+            Err(e) => format!("could not copy to the clipboard: {e}"),
+        });
+    }
+
+    /// The footer items in order, followed by the line still being typed.
+    fn footer_text(&self) -> String {
+        let mut text = footer::join_items(&self.footer_items);
+        if !self.footer.value.trim().is_empty() {
+            if !text.is_empty() {
+                text.push('\n');
+            }
+            text.push_str(&self.footer.value);
+        }
+        text
+    }
+
+    /// Replace the footer with the items parsed from `text`.
+    fn set_footer(&mut self, text: &str) {
+        self.footer_items = footer::parse_items(text);
+        self.footer_selected = None;
+        self.footer = TextInput::new();
+    }
+
+    /// Turn the typed footer line into items, after the selected one.
+    fn add_footer_item(&mut self) {
+        let items = footer::parse_items(&self.footer.value);
+        let at = self
+            .footer_selected
+            .map_or(self.footer_items.len(), |i| i + 1);
+        self.footer_selected = None;
+        self.footer = TextInput::new();
+        self.footer_items.splice(at..at, items);
+    }
+
+    /// Start a new footer line as a copy of the selected item, of the line
+    /// being typed (which is added first), or else of the last item.
+    fn duplicate_footer_line(&mut self) {
+        let typed = !self.footer.value.trim().is_empty();
+        let copy = match self.footer_selected {
+            Some(i) => self.footer_items[i].to_string(),
+            None if typed => self.footer.value.clone(),
+            None => match self.footer_items.last() {
+                Some(item) => item.to_string(),
+                None => return,
+            },
+        };
+        if typed {
+            self.add_footer_item();
+        }
+        self.footer_selected = None;
+        self.footer = TextInput::from(copy);
+    }
+
+    /// The fields as they go into the message: scope and description trimmed,
+    /// body wrapped, and sections the type forbids left empty.
+    fn message_parts(&self) -> ParsedMessage {
+        // normalized here, so the inputs keep what was typed
+        let collapse = self.config.collapse_spaces;
+        let mut body = match self.config.body_wrap_width {
+            _ if !self.field_enabled(Focus::Body) => String::new(),
+            Some(width) => text::wrap_text(&self.body.value, width),
+            None => self.body.value.clone(),
+        };
+        if self.config.separate_body_blocks {
+            body = text::separate_blocks(&body);
+        }
+        let mut footer = if self.field_enabled(Focus::Footer) {
+            self.footer_text()
+        } else {
+            String::new()
+        };
+        let mut description = text::normalize_field(&self.description.value, collapse);
+        let ticket = self.ticket.value.trim();
+        if let Some(config) = &self.config.ticket
+            && !ticket.is_empty()
+        {
+            let rendered = config.render(ticket);
+            match config.placement {
+                TicketPlacement::Description => description.insert_str(0, &rendered),
+                TicketPlacement::Footer => {
+                    if !footer.is_empty() {
+                        footer.push('\n');
+                    }
+                    footer.push_str(&rendered);
+                }
+            }
+        }
+        if self.field_enabled(Focus::Footer) {
+            footer = footer::with_branch_trailer(&footer, self.branch.as_deref());
+        }
+        if let Some(tag) = self.automation_tag() {
+            match self.config.automation_tag_placement {
+                TagPlacement::Header => {
+                    description.push(' ');
+                    description.push_str(tag);
+                }
+                TagPlacement::Footer => {
+                    if !footer.is_empty() {
+                        footer.push('\n');
+                    }
+                    footer.push_str(tag);
+                }
+            }
+        }
+        ParsedMessage {
+            commit_type: self.types[self.type_idx].name.clone(),
+            scope: scope_or_placeholder(
+                self.normalized_scope(),
+                self.config.empty_scope_placeholder.as_deref(),
+            ),
+            breaking: self.breaking,
+            description,
+            body,
+            footer,
+        }
+    }
+
+    fn automation_tag(&self) -> Option<&str> {
+        let tag = self.config.automation_tags.get(self.automation_tag?)?;
+        Some(tag.as_str())
+    }
+
+    /// Ctrl+T: move on to the next configured automation tag, and from the
+    /// last one back to none.
+    fn cycle_automation_tag(&mut self) {
+        let count = self.config.automation_tags.len();
+        self.automation_tag = match self.automation_tag {
+            None if count > 0 => Some(0),
+            Some(i) if i + 1 < count => Some(i + 1),
+            _ => None,
+        };
+        self.notice = Some(match self.automation_tag() {
+            Some(tag) => format!("automation tag: {tag}"),
+            None if count == 0 => "no automation_tags configured".to_string(),
+            None => "no automation tag".to_string(),
+        });
+    }
+
+    /// The rest of the most used known scope that starts with what is typed
+    /// in the Scope field, while the cursor is at its end.
+    fn scope_suggestion(&self) -> Option<&str> {
+        let typed = &self.scope.value;
+        if self.focus != Focus::Scope || typed.is_empty() || self.scope.cursor != typed.len() {
+            return None;
+        }
+        self.scopes
+            .iter()
+            .find_map(|s| s.name.strip_prefix(typed.as_str()))
+            .filter(|rest| !rest.is_empty())
+    }
+
+    /// The scope `scopes.txt` gains when the session ends with `outcome`: one
+    /// that was typed rather than picked, once a message using it is
+    /// written. Scopes added with `+` are saved right away instead.
+    fn scope_to_save(&self, outcome: Outcome) -> Option<Scope> {
+        if outcome != Outcome::Written
+            || !self.config.save_typed_scopes
+            || !self.field_enabled(Focus::Scope)
+        {
+            return None;
+        }
+        let name = self.normalized_scope();
+        if name.is_empty() || self.scopes.iter().any(|s| s.name == name) {
+            return None;
+        }
+        Some(Scope {
+            name,
+            description: None,
+            uses: 0,
+            detected: false,
+        })
+    }
+
+    /// The scope as it goes into the message: trimmed, and spelled per
+    /// `scope_case`.
+    fn normalized_scope(&self) -> String {
+        let scope = text::normalize_field(&self.scope.value, self.config.collapse_spaces);
+        match self.config.scope_case {
+            Some(case) => scopes::normalize_scope(&scope, case),
+            None => scope,
+        }
+    }
+
+    /// The input a `[max_length]` limit applies to for `focus`, whether it
+    /// counts per line, and the limit.
+    fn length_limit(&self, focus: Focus) -> Option<(&TextInput, bool, FieldLimit)> {
+        let (field, input, per_line) = match focus {
+            Focus::Scope => (LimitedField::Scope, &self.scope, false),
+            Focus::Description => (LimitedField::Description, &self.description, false),
+            Focus::Body => (LimitedField::BodyLine, &self.body, true),
+            _ => return None,
+        };
+        let limit = *self.config.max_length.get(&field)?;
+        Some((input, per_line, limit))
+    }
+
+    fn commit_message(&self) -> String {
+        let emoji = self.types[self.type_idx].emoji.as_deref().unwrap_or("");
+        build_message(
+            &self.message_parts(),
+            &FormatOptions::from_config(&self.config, emoji),
+        )
+    }
+}
+
+/// `type(scope)` of a parsed header, or just `type`.
+fn header_context(parts: &ParsedMessage) -> String {
+    if parts.scope.is_empty() {
+        parts.commit_type.clone()
+    } else {
+        format!("{}({})", parts.commit_type, parts.scope)
+    }
+}
+
+/// Conventional commit types are limited to `[a-z0-9-]+`.
+fn is_valid_type_name(name: &str) -> bool {
+    !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-')
+}
+
+// ---------- persistence helpers ----------
+fn components_dir() -> PathBuf {
+    preform_dir().join("components")
+}
+
+fn persist_new_type(name: &str) -> Result<()> {
+    fs::create_dir_all(components_dir()).context("creating components dir failed")?;
+    let p = components_dir().join(name);
+    if !p.exists() {
+        File::create(p).context("creating type file failed")?;
+    }
+    Ok(())
+}
+
+/// Delete the component file of type `name`, unless it is the only one left:
+/// without any the form falls back to the built-in types. Returns whether a
+/// file was removed.
+fn remove_type(name: &str) -> Result<bool> {
+    let path = components_dir().join(name);
+    if !is_valid_type_name(name) || !path.is_file() {
+        return Ok(false);
+    }
+    let remaining = fs::read_dir(components_dir())
+        .context("reading components dir failed")?
+        .filter_map(Result::ok)
+        .count();
+    if remaining <= 1 {
+        bail!("refusing to remove `{name}`, the last type; add another one first");
+    }
+    fs::remove_file(path).context("removing type file failed")?;
+    Ok(true)
+}
+
+/// The description lives in the type's component file.
+fn persist_type_description(name: &str, description: &str) -> Result<()> {
+    fs::create_dir_all(components_dir()).context("creating components dir failed")?;
+    let contents = if description.is_empty() {
+        String::new()
+    } else {
+        format!("{description}\n")
+    };
+    fs::write(components_dir().join(name), contents).context("writing type file failed")?;
+    Ok(())
+}
+
+// ---------- UI ----------
+
+/// Shorten `s` to at most `max_width` terminal columns, ending in `…` when cut.
+fn truncate_with_ellipsis(s: &str, max_width: usize) -> String {
+    if s.width() <= max_width {
+        return s.to_string();
+    }
+    if max_width == 0 {
+        return String::new();
+    }
+    let mut out = String::new();
+    let mut used = 0;
+    for c in s.chars() {
+        let w = c.width().unwrap_or(0);
+        if used + w > max_width - 1 {
+            break;
+        }
+        out.push(c);
+        used += w;
+    }
+    out.push('…');
+    out
+}
+
+/// Keep the end of a path (the part that identifies it) within `max_width` columns.
+fn abbreviate_path(path: &str, max_width: usize) -> String {
+    if path.width() <= max_width {
+        return path.to_string();
+    }
+    if max_width == 0 {
+        return String::new();
+    }
+    let mut tail = Vec::new();
+    let mut used = 0;
+    for c in path.chars().rev() {
+        let w = c.width().unwrap_or(0);
+        if used + w > max_width - 1 {
+            break;
+        }
+        tail.push(c);
+        used += w;
+    }
+    std::iter::once('…').chain(tail.into_iter().rev()).collect()
+}
+
+/// Where commits of the selected type show up downstream, once
+/// `[changelog_sections]` is configured.
+fn changelog_hint(app: &App) -> Option<Line<'static>> {
+    if app.config.changelog_sections.is_empty() {
+        return None;
+    }
+    let section = match &app.types[app.type_idx].changelog_section {
+        Some(section) => format!("→ changelog: {section}"),
+        None => "→ not in the changelog".to_string(),
+    };
+    Some(Line::styled(section, Style::default().fg(Color::Cyan)).right_aligned())
+}
+
+fn type_title(app: &App, style: Style) -> Line<'static> {
+    let mut spans = vec![
+        field_number(app, Focus::Type),
+        Span::styled("Type  ( + to add, e to describe, Alt+B breaking )", style),
+    ];
+    if app.breaking {
+        spans.push(Span::raw(" "));
+        spans.push(Span::styled(
+            " ! BREAKING ",
+            Style::default()
+                .fg(Color::White)
+                .bg(Color::Red)
+                .add_modifier(Modifier::BOLD),
+        ));
+    }
+    Line::from(spans)
+}
+
+/// The field's position for Alt+digit, as a dim title prefix.
+fn field_number(app: &App, focus: Focus) -> Span<'static> {
+    let n = app
+        .field_order()
+        .iter()
+        .position(|f| *f == focus)
+        .unwrap_or(0)
+        + 1;
+    Span::styled(
+        format!("{n} "),
+        Style::default().add_modifier(Modifier::DIM),
+    )
+}
+
+/// Whether the selected type needs a scope, as a title badge.
+fn scope_requirement(app: &App) -> Span<'static> {
+    if app.types[app.type_idx].require_scope {
+        Span::styled(" (required)", Style::default().fg(Color::Red))
+    } else {
+        Span::styled(" (optional)", Style::default().add_modifier(Modifier::DIM))
+    }
+}
+
+/// Hunk rows PageUp/PageDown scroll by.
+const HUNK_PAGE: usize = 5;
+
+/// Terminals narrower than this get the form only, without the context panel.
+const CONTEXT_PANEL_MIN_WIDTH: u16 = 100;
+
+/// Read-only view of what is about to be committed.
+fn draw_context_panel(f: &mut Frame, app: &App, area: Rect) {
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title("Staged changes");
+    let para = match app.staged_stat.as_deref() {
+        None => Paragraph::new(Span::styled(
+            "not a git repository",
+            Style::default().add_modifier(Modifier::DIM),
+        )),
+        Some("") => Paragraph::new(Span::styled(
+            "nothing staged (git add …)",
+            Style::default().add_modifier(Modifier::DIM),
+        )),
+        Some(stat) => Paragraph::new(stat),
+    };
+    if !app.config.show_hunks {
+        f.render_widget(para.block(block), area);
+        return;
+    }
+    let rows = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Percentage(40), Constraint::Percentage(60)])
+        .split(area);
+    f.render_widget(para.block(block), rows[0]);
+    draw_hunks(f, app, rows[1]);
+}
+
+/// Where the staged changes are: one row per hunk with the function Git
+/// found around it, scrolled by PageUp/PageDown.
+fn draw_hunks(f: &mut Frame, app: &App, area: Rect) {
+    let dim = Style::default().add_modifier(Modifier::DIM);
+    let title = format!("Changed hunks ({})  PgUp/PgDn", app.hunks.len());
+    let block = Block::default().borders(Borders::ALL).title(title);
+    // only the visible rows are built, however large the diff
+    let height = area.height.saturating_sub(2) as usize;
+    let lines: Vec<Line> = app
+        .hunks
+        .iter()
+        .skip(app.hunk_scroll)
+        .take(height)
+        .map(|location| match location {
+            git::ChangeLocation::Hunk {
+                file,
+                line,
+                context,
+            } => Line::from(vec![
+                Span::raw(format!("{file}:{line}")),
+                Span::styled(format!("  {context}"), dim),
+            ]),
+            git::ChangeLocation::Binary { file } => {
+                Line::from(vec![Span::raw(file.clone()), Span::styled("  binary", dim)])
+            }
+        })
+        .collect();
+    let para = if lines.is_empty() {
+        Paragraph::new(Span::styled("no hunks staged", dim))
+    } else {
+        Paragraph::new(lines)
+    };
+    f.render_widget(para.block(block), area);
+}
+
+/// Compact mode: one header input with a live breakdown of what it parses to.
+fn draw_compact(f: &mut Frame, app: &App, input: &TextInput) {
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .margin(1)
+        .constraints([
+            Constraint::Length(3),
+            Constraint::Length(6),
+            Constraint::Length(1),
+            Constraint::Min(0),
+        ])
+        .split(f.area());
+
+    let block = Block::default().borders(Borders::ALL).title(Span::styled(
+        "Header  type(scope)!: description  (Tab completes, Enter to commit, Esc to abort)",
+        Style::default().add_modifier(Modifier::BOLD),
+    ));
+    f.render_widget(Paragraph::new(input.value.as_str()).block(block), chunks[0]);
+    let x = chunks[0].x + 1 + input.cursor_offset().0;
+    f.set_cursor_position(Position::new(x, chunks[0].y + 1));
+
+    let dim = Style::default().add_modifier(Modifier::DIM);
+    // typed the Conventional Commits way, whatever `scope_format` writes
+    let preview = match parse_header(&input.value, ScopeFormat::Parens) {
+        Some(parsed) => {
+            let known = app.types.iter().any(|t| t.name == parsed.commit_type);
+            let field = |label: &'static str, value: String| {
+                Line::from(vec![
+                    Span::styled(format!("{label:<12}"), dim),
+                    Span::raw(value),
+                ])
+            };
+            let mut type_line = field("Type", parsed.commit_type.clone());
+            if !known {
+                type_line.push_span(Span::styled("  (unknown)", Style::default().fg(Color::Red)));
+            }
+            Text::from(vec![
+                type_line,
+                field("Scope", parsed.scope.clone()),
+                field(
+                    "Breaking",
+                    if parsed.breaking { "yes" } else { "no" }.into(),
+                ),
+                field("Description", parsed.description.clone()),
+            ])
+        }
+        None => Text::from(Span::styled("not a conventional header yet", dim)),
+    };
+    let preview =
+        Paragraph::new(preview).block(Block::default().borders(Borders::ALL).title("Parsed"));
+    f.render_widget(preview, chunks[1]);
+
+    if let Some(issue) = &app.issue {
+        let status = Span::styled(
+            format!("✗ {}", issue.message),
+            Style::default().fg(Color::Red),
+        );
+        f.render_widget(Paragraph::new(status), chunks[2]);
+    } else if let Some(notice) = &app.notice {
+        f.render_widget(Paragraph::new(notice.as_str()), chunks[2]);
+    }
+}
+
+fn draw_ui(f: &mut Frame, app: &App) {
+    if let Some(input) = &app.compact {
+        draw_compact(f, app, input);
+        return;
+    }
+    let area = f.area();
+    let mut form_area = area;
+    if area.width >= CONTEXT_PANEL_MIN_WIDTH {
+        let columns = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Percentage(60), Constraint::Percentage(40)])
+            .split(area);
+        form_area = columns[0];
+        let panel = columns[1].inner(Margin {
+            horizontal: 1,
+            vertical: 1,
+        });
+        draw_context_panel(f, app, panel);
+    }
+    // Text inputs; fields the selected type forbids are left out entirely
+    let inputs: Vec<(&str, &TextInput, Focus)> = [
+        ("Scope  ( + to add, ↓ to pick )", &app.scope, Focus::Scope),
+        (
+            if app.config.freeform {
+                "Subject"
+            } else {
+                "Description"
+            },
+            &app.description,
+            Focus::Description,
+        ),
+        ("Ticket", &app.ticket, Focus::Ticket),
+        ("Body", &app.body, Focus::Body),
+        ("Footer", &app.footer, Focus::Footer),
+    ]
+    .into_iter()
+    .filter(|(_, _, focus)| app.field_enabled(*focus))
+    .collect();
+
+    let type_rows = app.types.len()
+        + app
+            .types
+            .iter()
+            .filter_map(|t| t.group.as_ref())
+            .collect::<std::collections::HashSet<_>>()
+            .len();
+    // fit the list to its rows, up to the cap; longer lists scroll
+    let cap = app.config.max_visible_items.unwrap_or(TYPE_LIST_ROWS);
+    let type_height = if app.config.freeform {
+        0
+    } else {
+        type_rows.clamp(1, cap.max(1)) as u16 + 2
+    };
+    let mut constraints = vec![
+        Constraint::Length(1),        // readiness strip
+        Constraint::Max(type_height), // Type list
+    ];
+    for (_, _, focus) in &inputs {
+        constraints.push(match focus {
+            Focus::Body => Constraint::Min(3),
+            // one row per footer item, plus the line being typed
+            Focus::Footer => Constraint::Length(footer_item_rows(app) + 3),
+            _ => Constraint::Length(3),
+        });
+    }
+    let preview = app.config.subject_preview_width > 0;
+    if preview {
+        constraints.push(Constraint::Length(1)); // `git log --oneline` preview
+    }
+    constraints.push(Constraint::Length(1)); // Status line
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .margin(1)
+        .constraints(constraints)
+        .split(form_area);
+    f.render_widget(Paragraph::new(readiness_strip(app)), chunks[0]);
+    let chunks = &chunks[1..];
+    let status_area = chunks[chunks.len() - 1];
+    if preview {
+        f.render_widget(
+            Paragraph::new(oneline_preview(app)),
+            chunks[chunks.len() - 2],
+        );
+    }
+
+    // Types list (dropdown-like); names are shortened for display only
+    let theme = &app.config.theme;
+    let name_width = (chunks[0].width as usize).saturating_sub(2 + theme.highlight_symbol.width());
+    // Group headers are interleaved as plain rows; type_idx only ever points at types.
+    let mut items: Vec<ListItem> = Vec::new();
+    let mut selected_row = 0;
+    let mut current_group = None;
+    for (i, t) in app.types.iter().enumerate() {
+        if t.group != current_group {
+            if let Some(group) = &t.group {
+                items.push(ListItem::new(Span::styled(
+                    truncate_with_ellipsis(group, name_width),
+                    Style::default().add_modifier(Modifier::DIM | Modifier::ITALIC),
+                )));
+            }
+            current_group = t.group.clone();
+        }
+        if i == app.type_idx {
+            selected_row = items.len();
+        }
+        let name = truncate_with_ellipsis(&t.name, name_width);
+        let mut spans = vec![Span::raw(name.clone())];
+        if let Some(description) = &t.description {
+            let room = name_width.saturating_sub(name.width() + 3);
+            if room > 0 {
+                spans.push(Span::styled(
+                    format!(" — {}", truncate_with_ellipsis(description, room)),
+                    Style::default().add_modifier(Modifier::DIM),
+                ));
+            }
+        }
+        items.push(ListItem::new(Line::from(spans)));
+    }
+    let mut state = ListState::default();
+    state.select(Some(selected_row));
+    let title_style = if app.focus == Focus::Type {
+        Style::default().add_modifier(Modifier::BOLD)
+    } else {
+        Style::default()
+    };
+    let mut type_block = Block::default()
+        .borders(Borders::ALL)
+        .title(type_title(app, title_style));
+    if let Some(hint) = changelog_hint(app) {
+        type_block = type_block.title_bottom(hint);
+    }
+    let list = List::new(items)
+        .block(type_block)
+        .highlight_symbol(&theme.highlight_symbol)
+        .highlight_style(theme.highlight_style);
+    if !app.config.freeform {
+        f.render_stateful_widget(list, chunks[0], &mut state);
+    }
+
+    // after a refused Enter, each field shows what is still wrong with it;
+    // warnings are shown all along since they never block
+    let issues: Vec<Issue> = validate(app)
+        .into_iter()
+        .filter(|issue| app.show_issues || issue.severity == Severity::Warn)
+        .collect();
+    for (i, (label, ti, focus)) in inputs.iter().enumerate() {
+        let mut title = vec![
+            field_number(app, *focus),
+            Span::styled(
+                *label,
+                if app.focus == *focus {
+                    Style::default().add_modifier(Modifier::BOLD)
+                } else {
+                    Style::default()
+                },
+            ),
+        ];
+        if *focus == Focus::Scope {
+            title.push(scope_requirement(app));
+            // the message gets the normalized spelling; say so, and how to adopt it
+            let normalized = app.normalized_scope();
+            if app.config.scope_case.is_some() && normalized != app.scope.value.trim() {
+                title.push(Span::styled(
+                    format!("  → {normalized} (Alt+N)"),
+                    Style::default().fg(Color::Yellow),
+                ));
+            }
+        }
+        if let Some((input, per_line, limit)) = app.length_limit(*focus) {
+            let length = text::limited_length(&input.value, input.cursor, per_line);
+            let style = if length > limit.max {
+                Style::default().fg(Color::Red)
+            } else {
+                Style::default().add_modifier(Modifier::DIM)
+            };
+            title.push(Span::styled(format!("  {length}/{}", limit.max), style));
+        }
+        if *focus == Focus::Footer && app.focus == Focus::Footer {
+            title.push(Span::styled(
+                "  Alt+Enter add · ↑↓ select · Alt+↑↓ move · Alt+D copy · Ctrl+D remove",
+                Style::default().add_modifier(Modifier::DIM),
+            ));
+        }
+        if *focus == Focus::Body {
+            let body = &app.body.value;
+            title.push(Span::styled(
+                format!(
+                    "  words: {} · lines: {}",
+                    body.split_whitespace().count(),
+                    body.lines().count()
+                ),
+                Style::default().add_modifier(Modifier::DIM),
+            ));
+        }
+        let mut block = Block::default()
+            .borders(Borders::ALL)
+            .title(Line::from(title));
+        let field_issues: Vec<&Issue> = app
+            .issue
+            .iter()
+            .chain(&issues)
+            .filter(|issue| issue.field == *focus)
+            .collect();
+        if !field_issues.is_empty() {
+            let blocking = field_issues
+                .iter()
+                .any(|issue| issue.severity == Severity::Error);
+            let (mark, color) = if blocking {
+                ("✗", Color::Red)
+            } else {
+                ("!", Color::Yellow)
+            };
+            let mut messages: Vec<&str> = field_issues
+                .iter()
+                .map(|issue| issue.message.as_str())
+                .collect();
+            messages.dedup();
+            block = block
+                .border_style(Style::default().fg(color))
+                .title_bottom(Span::styled(
+                    format!("{mark} {}", messages.join(" · ")),
+                    Style::default().fg(color),
+                ));
+        }
+        let mut text = if *focus == Focus::Footer {
+            footer_text(app)
+        } else {
+            Text::from(ti.value.as_str())
+        };
+        if *focus == Focus::Scope
+            && let Some(rest) = app.scope_suggestion()
+        {
+            text.push_span(Span::styled(
+                rest.to_string(),
+                Style::default().add_modifier(Modifier::DIM),
+            ));
+        }
+        if *focus == Focus::Scope
+            && let Some(description) = app
+                .scopes
+                .iter()
+                .find(|s| s.name == ti.value)
+                .and_then(|s| s.description.as_deref())
+        {
+            text.push_span(Span::styled(
+                format!("  — {description}"),
+                Style::default().add_modifier(Modifier::DIM),
+            ));
+        }
+        let mut field_area = chunks[i + 1];
+        if *focus == Focus::Body && app.body_preview {
+            let halves = Layout::default()
+                .direction(Direction::Horizontal)
+                .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
+                .split(field_area);
+            field_area = halves[0];
+            let preview = Paragraph::new(markdown::render(&app.body.value))
+                .wrap(Wrap { trim: false })
+                .block(
+                    Block::default()
+                        .borders(Borders::ALL)
+                        .title("Preview (Alt+M to hide)")
+                        .border_style(Style::default().add_modifier(Modifier::DIM)),
+                );
+            f.render_widget(preview, halves[1]);
+        }
+        let para = Paragraph::new(text).block(block);
+        f.render_widget(para, field_area);
+        // no cursor while a footer item is selected
+        if app.focus == *focus
+            && app.overlay.is_none()
+            && !(*focus == Focus::Footer && app.footer_selected.is_some())
+        {
+            // cursor inside the block (1 char padding)
+            let (col, mut row) = ti.cursor_offset();
+            if *focus == Focus::Footer {
+                row += footer_item_rows(app);
+            }
+            let x = (field_area.x + 1 + col).min(field_area.right().saturating_sub(2));
+            let y = field_area.y + 1 + row;
+            f.set_cursor_position(Position::new(x, y));
+        }
+    }
+
+    // Status line: a blocking issue, then any notice, otherwise where the message
+    // goes ("→ " takes two columns)
+    let status = match (&app.issue, &app.notice) {
+        (Some(issue), _) => Span::styled(
+            format!("✗ {}", issue.message),
+            Style::default().fg(Color::Red),
+        ),
+        (None, Some(notice)) => {
+            Span::styled(format!("! {notice}"), Style::default().fg(Color::Yellow))
+        }
+        (None, None) => {
+            let target = abbreviate_path(
+                &app.hook_path.display().to_string(),
+                (status_area.width as usize).saturating_sub(2),
+            );
+            Span::styled(
+                format!("→ {target}"),
+                Style::default().add_modifier(Modifier::DIM),
+            )
+        }
+    };
+    let mut indicators = String::new();
+    if let Some((staged, unstaged)) = app.change_counts
+        && staged + unstaged > 0
+    {
+        indicators.push_str(&format!("{staged} staged · {unstaged} unstaged "));
+    }
+    if app.signing {
+        indicators.push_str("[signed] ");
+    }
+    let status_row = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([
+            Constraint::Min(0),
+            Constraint::Length(indicators.width() as u16),
+        ])
+        .split(status_area);
+    f.render_widget(Paragraph::new(status), status_row[0]);
+    f.render_widget(
+        Paragraph::new(Span::styled(
+            indicators,
+            Style::default().add_modifier(Modifier::DIM),
+        )),
+        status_row[1],
+    );
+
+    if app.overlay.is_some() {
+        // dim the form so the overlay drawn over it stands out
+        let backdrop = Block::default().style(Style::default().add_modifier(Modifier::DIM));
+        f.render_widget(backdrop, area);
+    }
+    if let Some(Overlay {
+        target:
+            OverlayTarget::LogPreview {
+                author,
+                date,
+                branch,
+            },
+        ..
+    }) = &app.overlay
+    {
+        draw_log_preview(f, app, author, date, branch.as_deref());
+        return;
+    }
+    if let Some(Overlay {
+        target: OverlayTarget::Help { scroll },
+        ..
+    }) = &app.overlay
+    {
+        draw_help(f, app, *scroll);
+        return;
+    }
+    if let Some(Overlay {
+        target: OverlayTarget::PickScope { selected },
+        input,
+    }) = &app.overlay
+    {
+        draw_scope_picker(f, app, input, *selected);
+        return;
+    }
+    if let Some(Overlay {
+        target: OverlayTarget::FilterTypes { selected },
+        input,
+    }) = &app.overlay
+    {
+        draw_type_filter(f, app, input, *selected);
+        return;
+    }
+    if let Some(Overlay {
+        target: OverlayTarget::QuickSwitch { selected },
+        input,
+    }) = &app.overlay
+    {
+        draw_quick_switch(f, app, input, *selected);
+        return;
+    }
+    if let Some(Overlay {
+        target: OverlayTarget::PickIntent { selected, names },
+        input,
+    }) = &app.overlay
+    {
+        draw_intent_picker(f, app, input, *selected, names);
+        return;
+    }
+    if let Some(Overlay {
+        target: OverlayTarget::PickDraft { selected, names },
+        input,
+    }) = &app.overlay
+    {
+        draw_draft_picker(f, app, input, *selected, names);
+        return;
+    }
+
+    // Overlay (modal) to add type/scope
+    if let Some(ov) = &app.overlay {
+        let popup = overlay_rect(area, 3);
+        f.render_widget(Clear, popup);
+
+        let title = match ov.target {
+            OverlayTarget::NewType => "New Type [a-z0-9-] (Enter to save, Esc to cancel)",
+            OverlayTarget::NewScope => "New Scope (Enter to save, Esc to cancel)",
+            OverlayTarget::EditTypeDescription => "Type Description (Enter to save, Esc to cancel)",
+            OverlayTarget::RestoreDraft(_) => {
+                "Restore unfinished draft? (y to restore, n to discard)"
+            }
+            OverlayTarget::SaveDraft => {
+                "Save Draft As [A-Za-z0-9._-] (Enter to save, Esc to cancel)"
+            }
+            OverlayTarget::ConfirmReset => "Clear all fields? (y to clear, n to keep)",
+            OverlayTarget::LogPreview { .. } => unreachable!("drawn by draw_log_preview"),
+            OverlayTarget::Help { .. } => unreachable!("drawn by draw_help"),
+            OverlayTarget::PickScope { .. }
+            | OverlayTarget::FilterTypes { .. }
+            | OverlayTarget::QuickSwitch { .. }
+            | OverlayTarget::PickIntent { .. }
+            | OverlayTarget::PickDraft { .. } => {
+                unreachable!("drawn as a picker")
+            }
+        };
+        let text = match &ov.target {
+            OverlayTarget::RestoreDraft(draft) => draft.description.as_str(),
+            _ => ov.input.value.as_str(),
+        };
+        let block = Block::default().borders(Borders::ALL).title(title);
+        let para = Paragraph::new(text).block(block);
+        f.render_widget(para, popup);
+
+        if !matches!(
+            ov.target,
+            OverlayTarget::RestoreDraft(_) | OverlayTarget::ConfirmReset
+        ) {
+            let x = popup.x + 1 + ov.input.cursor_offset().0;
+            let x = x.min(popup.right().saturating_sub(2));
+            f.set_cursor_position(Position::new(x, popup.y + 1));
+        }
+    }
+}
+
+/// Name spans with fuzzy matches highlighted, followed by a dim description
+/// if there is room for it within `width` columns.
+fn picker_row(
+    name: &str,
+    positions: &[usize],
+    tag: Option<Span<'static>>,
+    description: Option<&str>,
+    width: usize,
+) -> ListItem<'static> {
+    let name = truncate_with_ellipsis(name, width);
+    let mut used = name.width();
+    let mut spans = fuzzy::highlight(&name, positions);
+    if let Some(tag) = tag {
+        used += tag.width();
+        spans.push(tag);
+    }
+    if let Some(description) = description {
+        let room = width.saturating_sub(used + 3);
+        if room > 0 {
+            spans.push(Span::styled(
+                format!(" — {}", truncate_with_ellipsis(description, room)),
+                Style::default().add_modifier(Modifier::DIM),
+            ));
+        }
+    }
+    ListItem::new(Line::from(spans))
+}
+
+/// Whether the message could be written now: red with the number of
+/// blocking issues while `validate` objects, green once it doesn't, with any
+/// warnings counted alongside. Amends lead with a banner naming the commit.
+fn readiness_strip(app: &App) -> Line<'static> {
+    let issues = validate(app);
+    let warnings = issues
+        .iter()
+        .filter(|issue| issue.severity == Severity::Warn)
+        .count();
+    let (mut text, color) = match issues.len() - warnings {
+        0 => ("● ready to commit".to_string(), Color::Green),
+        1 => ("● incomplete · 1 issue".to_string(), Color::Red),
+        n => (format!("● incomplete · {n} issues"), Color::Red),
+    };
+    match warnings {
+        0 => {}
+        1 => text.push_str(" · 1 warning"),
+        n => text.push_str(&format!(" · {n} warnings")),
+    }
+    let mut spans = Vec::new();
+    if let Some(sha) = &app.amending {
+        spans.push(Span::styled(
+            format!(" Amending commit {sha} "),
+            Style::default()
+                .fg(Color::Black)
+                .bg(Color::Yellow)
+                .add_modifier(Modifier::BOLD),
+        ));
+        spans.push(Span::raw("  "));
+    }
+    spans.push(Span::styled(text, Style::default().fg(color)));
+    Line::from(spans)
+}
+
+/// The subject as `git log --oneline` would list it, greyed past
+/// `subject_preview_width` characters.
+fn oneline_preview(app: &App) -> Line<'static> {
+    let message = app.commit_message();
+    let subject = message.lines().next().unwrap_or("");
+    let split = subject
+        .char_indices()
+        .nth(app.config.subject_preview_width)
+        .map_or(subject.len(), |(i, _)| i);
+    let (fits, overflow) = subject.split_at(split);
+    Line::from(vec![
+        Span::styled("git log: ", Style::default().add_modifier(Modifier::DIM)),
+        Span::raw(fits.to_string()),
+        Span::styled(overflow.to_string(), Style::default().fg(Color::DarkGray)),
+    ])
+}
+
+/// Rows taken by the footer items above the input line.
+fn footer_item_rows(app: &App) -> u16 {
+    app.footer_items
+        .iter()
+        .map(|item| item.to_string().lines().count() as u16)
+        .sum()
+}
+
+/// Footer items, trailer tokens in bold and the selected item highlighted,
+/// followed by the line being typed.
+fn footer_text(app: &App) -> Text<'static> {
+    let theme = &app.config.theme;
+    let mut text = Text::default();
+    for (i, item) in app.footer_items.iter().enumerate() {
+        let selected = app.focus == Focus::Footer && app.footer_selected == Some(i);
+        let style = if selected {
+            theme.highlight_style
+        } else {
+            Style::default()
+        };
+        let rendered = item.to_string();
+        for (n, line) in rendered.lines().enumerate() {
+            let mut spans = Vec::new();
+            if selected && n == 0 {
+                spans.push(Span::styled(theme.highlight_symbol.clone(), style));
+            }
+            match item {
+                FooterItem::Trailer { token, .. } if n == 0 => {
+                    spans.push(Span::styled(
+                        token.clone(),
+                        style.add_modifier(Modifier::BOLD),
+                    ));
+                    spans.push(Span::styled(line[token.len()..].to_string(), style));
+                }
+                _ => spans.push(Span::styled(line.to_string(), style)),
+            }
+            text.lines.push(Line::from(spans));
+        }
+    }
+    for line in app.footer.value.split('\n') {
+        text.lines.push(Line::from(line.to_string()));
+    }
+    text
+}
+
+/// Centered list of known scopes with their descriptions, filtered by `filter`.
+fn draw_scope_picker(f: &mut Frame, app: &App, filter: &TextInput, selected: usize) {
+    let scopes = app.filtered_scopes(&filter.value);
+    draw_picker(
+        f,
+        app,
+        "Pick Scope (type to filter, Enter to pick, Esc to cancel)",
+        filter,
+        selected,
+        |width| {
+            scopes
+                .iter()
+                .map(|(s, positions)| {
+                    let tag = s
+                        .detected
+                        .then(|| Span::styled(" (staged)", Style::default().fg(Color::Cyan)));
+                    picker_row(&s.name, positions, tag, s.description.as_deref(), width)
+                })
+                .collect()
+        },
+    );
+}
+
+/// Centered list of types fuzzy-matching `filter`.
+fn draw_type_filter(f: &mut Frame, app: &App, filter: &TextInput, selected: usize) {
+    let types = app.filtered_types(&filter.value);
+    draw_picker(
+        f,
+        app,
+        "Find Type (type to filter, Enter to pick, Esc to cancel)",
+        filter,
+        selected,
+        |width| {
+            types
+                .iter()
+                .map(|(i, positions)| {
+                    let t = &app.types[*i];
+                    picker_row(&t.name, positions, None, t.description.as_deref(), width)
+                })
+                .collect()
+        },
+    );
+}
+
+/// Centered list of the types and then the scopes fuzzy-matching `filter`,
+/// each tagged with its kind.
+fn draw_quick_switch(f: &mut Frame, app: &App, filter: &TextInput, selected: usize) {
+    let matches = app.quick_switch(&filter.value);
+    let kind = |name: &'static str| Some(Span::styled(name, Style::default().fg(Color::Cyan)));
+    draw_picker(
+        f,
+        app,
+        "Switch Type or Scope (type to filter, Enter to pick, Esc to cancel)",
+        filter,
+        selected,
+        |width| {
+            matches
+                .iter()
+                .map(|(found, positions)| match found {
+                    Switch::Type(i) => {
+                        let t = &app.types[*i];
+                        let tag = kind(" type");
+                        picker_row(&t.name, positions, tag, t.description.as_deref(), width)
+                    }
+                    Switch::Scope(s) => {
+                        let tag = kind(" scope");
+                        picker_row(&s.name, positions, tag, s.description.as_deref(), width)
+                    }
+                })
+                .collect()
+        },
+    );
+}
+
+/// Centered list of the configured intents fuzzy-matching `filter`.
+fn draw_intent_picker(
+    f: &mut Frame,
+    app: &App,
+    filter: &TextInput,
+    selected: usize,
+    names: &[String],
+) {
+    let intents = app.filtered_names(names, &filter.value);
+    draw_picker(
+        f,
+        app,
+        "Start From Intent (type to filter, Enter to apply, Esc to start blank)",
+        filter,
+        selected,
+        |width| {
+            intents
+                .iter()
+                .map(|(name, positions)| {
+                    let description = app.config.intents[*name].description.as_deref();
+                    picker_row(name, positions, None, description, width)
+                })
+                .collect()
+        },
+    );
+}
+
+/// Centered list of saved drafts fuzzy-matching `filter`.
+fn draw_draft_picker(
+    f: &mut Frame,
+    app: &App,
+    filter: &TextInput,
+    selected: usize,
+    names: &[String],
+) {
+    let drafts = app.filtered_names(names, &filter.value);
+    draw_picker(
+        f,
+        app,
+        "Load Draft (type to filter, Enter to load, Esc to cancel)",
+        filter,
+        selected,
+        |width| {
+            drafts
+                .iter()
+                .map(|(name, positions)| picker_row(name, positions, None, None, width))
+                .collect()
+        },
+    );
+}
+
+/// Widest an overlay gets; narrower terminals keep a 2-column margin each side.
+const OVERLAY_WIDTH: u16 = 60;
+/// Below this much room the margin is dropped so the overlay stays usable.
+const OVERLAY_MIN_WIDTH: u16 = 20;
+
+/// A box `height` rows tall (or as tall as fits) centered in `area`, as wide as
+/// `OVERLAY_WIDTH` allows.
+fn overlay_rect(area: Rect, height: u16) -> Rect {
+    centered_rect(area, OVERLAY_WIDTH, height)
+}
+
+/// `overlay_rect` for a box up to `max_width` wide.
+fn centered_rect(area: Rect, max_width: u16, height: u16) -> Rect {
+    let width = max_width
+        .min(area.width.saturating_sub(4))
+        .max(OVERLAY_MIN_WIDTH.min(area.width));
+    let height = height.min(area.height);
+    Rect {
+        x: area.x + (area.width - width) / 2,
+        y: area.y + (area.height - height) / 2,
+        width,
+        height,
+    }
+}
+
+/// Widest the `git log` preview gets: a 72-column body indented by four.
+const LOG_PREVIEW_WIDTH: u16 = 80;
+
+/// The message as `git log` would show the commit, with a placeholder hash.
+fn draw_log_preview(f: &mut Frame, app: &App, author: &str, date: &str, branch: Option<&str>) {
+    let mut decoration = " (HEAD".to_string();
+    if let Some(branch) = branch {
+        decoration.push_str(&format!(" -> {branch}"));
+    }
+    decoration.push(')');
+    let mut lines = vec![
+        Line::from(vec![
+            Span::styled(
+                format!("commit {}", "0".repeat(40)),
+                Style::default().fg(Color::Yellow),
+            ),
+            Span::styled(decoration, Style::default().fg(Color::Cyan)),
+        ]),
+        Line::from(format!("Author: {author}")),
+        Line::from(format!("Date:   {date}")),
+        Line::from(""),
+    ];
+    for line in app.commit_message().lines() {
+        lines.push(Line::from(format!("    {line}")));
+    }
+    let area = f.area();
+    let popup = centered_rect(area, LOG_PREVIEW_WIDTH, lines.len() as u16 + 2);
+    f.render_widget(Clear, popup);
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title("git log (Esc or Ctrl+G to close)");
+    f.render_widget(Paragraph::new(lines).block(block), popup);
+}
+
+/// Wide enough for a key and its description.
+const HELP_WIDTH: u16 = 64;
+
+/// The key cheatsheet: `GLOBAL_KEYS`, plus the keys whose meaning depends on
+/// the config.
+fn help_lines(app: &App) -> Vec<Line<'static>> {
+    let enter = if app.config.enter_writes {
+        "write the message and exit"
+    } else {
+        "next field; a new line in the Body"
+    };
+    let mut rows = vec![
+        (
+            app.config.confirm_key.to_string(),
+            "write the message and exit",
+        ),
+        ("Enter".to_string(), enter),
+        ("Tab".to_string(), "next field"),
+        ("Alt+1…9".to_string(), "jump to a field by its number"),
+        (
+            app.config.accept_suggestion_key.to_string(),
+            "take the dim scope suggestion",
+        ),
+    ];
+    rows.extend(
+        GLOBAL_KEYS
+            .iter()
+            .map(|(spec, _, help)| (global_key(spec).to_string(), *help)),
+    );
+    rows.push(("?".to_string(), "the same as F1, on the Type list"));
+    let width = rows.iter().map(|(key, _)| key.width()).max().unwrap_or(0);
+    rows.into_iter()
+        .map(|(key, help)| {
+            let pad = " ".repeat(width - key.width() + 2);
+            Line::from(vec![
+                Span::styled(key, Style::default().add_modifier(Modifier::BOLD)),
+                Span::raw(format!("{pad}{help}")),
+            ])
+        })
+        .collect()
+}
+
+fn help_rect(app: &App, area: Rect) -> Rect {
+    let height = help_lines(app).len() as u16 + 2;
+    centered_rect(area, HELP_WIDTH, height.min(area.height.saturating_sub(2)))
+}
+
+fn draw_help(f: &mut Frame, app: &App, scroll: usize) {
+    let popup = help_rect(app, f.area());
+    f.render_widget(Clear, popup);
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title("Keys (↑/↓ PgUp/PgDn to scroll, Esc or ? to close)");
+    let help = Paragraph::new(help_lines(app))
+        .block(block)
+        .scroll((scroll as u16, 0));
+    f.render_widget(help, popup);
+}
+
+fn open_help(app: &mut App) {
+    app.overlay = Some(Overlay {
+        target: OverlayTarget::Help { scroll: 0 },
+        input: TextInput::new(),
+    });
+}
+
+/// A filter input above a list, centered over the form. `rows` builds the list
+/// items for the given content width.
+fn draw_picker(
+    f: &mut Frame,
+    app: &App,
+    title: &str,
+    filter: &TextInput,
+    selected: usize,
+    rows: impl FnOnce(usize) -> Vec<ListItem<'static>>,
+) {
+    let area = f.area();
+    let theme = &app.config.theme;
+    let width =
+        (overlay_rect(area, 0).width as usize).saturating_sub(2 + theme.highlight_symbol.width());
+    let items = rows(width);
+    let visible = match app.config.max_visible_items {
+        Some(max) => items.len().min(max),
+        None => items.len(),
+    };
+    let height = (visible as u16 + 5).clamp(6, area.height.saturating_sub(2).max(6));
+    let popup = overlay_rect(area, height);
+    f.render_widget(Clear, popup);
+
+    let parts = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(3), Constraint::Min(0)])
+        .split(popup);
+    let input = Paragraph::new(filter.value.as_str())
+        .block(Block::default().borders(Borders::ALL).title(title));
+    f.render_widget(input, parts[0]);
+
+    let mut state = ListState::default();
+    state.select((!items.is_empty()).then_some(selected));
+    let list = List::new(items)
+        .block(Block::default().borders(Borders::ALL))
+        .highlight_symbol(&theme.highlight_symbol)
+        .highlight_style(theme.highlight_style);
+    f.render_stateful_widget(list, parts[1], &mut state);
+
+    let x = parts[0].x + 1 + filter.cursor_offset().0;
+    f.set_cursor_position(Position::new(x, parts[0].y + 1));
+}
+
+// helpers
+/// The lines of a message file Git prepared, minus its comment lines.
+fn uncommented_lines(raw: &str) -> Vec<&str> {
+    let comment = git::comment_prefix(raw);
+    raw.lines()
+        .filter(|l| !l.starts_with(comment.as_str()))
+        .collect()
+}
+
+fn current_input_mut(app: &mut App) -> Option<&mut TextInput> {
+    match app.focus {
+        Focus::Scope => Some(&mut app.scope),
+        Focus::Description => Some(&mut app.description),
+        Focus::Body => Some(&mut app.body),
+        Focus::Ticket => Some(&mut app.ticket),
+        Focus::Footer => Some(&mut app.footer),
+        _ => None,
+    }
+}
+
+fn maybe_open_overlay(app: &mut App) {
+    app.overlay = match app.focus {
+        Focus::Type => Some(Overlay {
+            target: OverlayTarget::NewType,
+            input: TextInput::new(),
+        }),
+        Focus::Scope => Some(Overlay {
+            target: OverlayTarget::NewScope,
+            input: TextInput::new(),
+        }),
+        _ => None,
+    };
+}
+
+/// Keys for the scope picker, type filter, quick switcher and the intent and
+/// draft pickers, which move a selection and filter as you type. Returns `false` when none is open.
+fn handle_picker_key(app: &mut App, key: KeyEvent) -> Result<bool> {
+    let Some(Overlay { target, input }) = &app.overlay else {
+        return Ok(false);
+    };
+    let filter = input.value.clone();
+    let (selected, count) = match target {
+        OverlayTarget::PickScope { selected } => (*selected, app.filtered_scopes(&filter).len()),
+        OverlayTarget::FilterTypes { selected } => (*selected, app.filtered_types(&filter).len()),
+        OverlayTarget::QuickSwitch { selected } => (*selected, app.quick_switch(&filter).len()),
+        OverlayTarget::PickIntent { selected, names }
+        | OverlayTarget::PickDraft { selected, names } => {
+            (*selected, app.filtered_names(names, &filter).len())
+        }
+        _ => return Ok(false),
+    };
+
+    let mut next = selected;
+    match key.code {
+        KeyCode::Esc => {
+            app.overlay = None;
+            return Ok(true);
+        }
+        KeyCode::Enter => {
+            let Some(Overlay { target, .. }) = app.overlay.take() else {
+                return Ok(true);
+            };
+            match target {
+                OverlayTarget::PickScope { .. } => {
+                    if let Some((scope, _)) = app.filtered_scopes(&filter).get(selected) {
+                        app.scope = TextInput::from(scope.name.clone());
+                        app.advance_to(Focus::Description);
+                    }
+                }
+                OverlayTarget::FilterTypes { .. } => {
+                    if let Some(&(idx, _)) = app.filtered_types(&filter).get(selected) {
+                        app.select_type(idx);
+                        if app.config.auto_advance {
+                            app.advance_to(app.next_focus());
+                        }
+                    }
+                }
+                OverlayTarget::QuickSwitch { .. } => {
+                    match app
+                        .quick_switch(&filter)
+                        .get(selected)
+                        .map(|(found, _)| found)
+                    {
+                        Some(Switch::Type(idx)) => {
+                            let idx = *idx;
+                            app.select_type(idx);
+                        }
+                        Some(Switch::Scope(scope)) => {
+                            app.scope = TextInput::from(scope.name.clone());
+                            app.advance_to(Focus::Description);
+                        }
+                        None => {}
+                    }
+                }
+                OverlayTarget::PickIntent { names, .. } => {
+                    if let Some((name, _)) = app.filtered_names(&names, &filter).get(selected) {
+                        let name = name.to_string();
+                        if let Err(e) = app.apply_intent(&name) {
+                            app.notice = Some(e);
+                        }
+                    }
+                }
+                OverlayTarget::PickDraft { names, .. } => {
+                    if let Some((name, _)) = app.filtered_names(&names, &filter).get(selected) {
+                        Draft::load_named(name)?.apply(app);
+                        app.notice = Some(format!("loaded draft `{name}`"));
+                    }
+                }
+                _ => {}
+            }
+            return Ok(true);
+        }
+        KeyCode::Up => next = selected.saturating_sub(1),
+        KeyCode::Down if selected + 1 < count => next += 1,
+        KeyCode::Backspace | KeyCode::Char(_) => next = 0,
+        _ => {}
+    }
+    if let Some(Overlay {
+        target:
+            OverlayTarget::PickScope { selected }
+            | OverlayTarget::FilterTypes { selected }
+            | OverlayTarget::QuickSwitch { selected }
+            | OverlayTarget::PickIntent { selected, .. }
+            | OverlayTarget::PickDraft { selected, .. },
+        input,
+    }) = &mut app.overlay
+    {
+        *selected = next;
+        match key.code {
+            KeyCode::Backspace => input.backspace(),
+            KeyCode::Char(c) => input.insert_char(c),
+            _ => {}
+        }
+    }
+    Ok(true)
+}
+
+/// Footer list keys: Alt+Enter adds the typed line as an item, Up/Down select
+/// an item, Alt+Up/Alt+Down move it and Ctrl+D removes it. Returns `false`
+/// for keys that aren't footer-specific.
+fn handle_footer_key(app: &mut App, key: KeyEvent) -> bool {
+    let alt = key.modifiers.contains(KeyModifiers::ALT);
+    let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+    let len = app.footer_items.len();
+    match (key.code, app.footer_selected) {
+        (KeyCode::Enter, _) if alt => app.add_footer_item(),
+        (KeyCode::Up, Some(i)) if alt && i > 0 => {
+            app.footer_items.swap(i, i - 1);
+            app.footer_selected = Some(i - 1);
+        }
+        (KeyCode::Down, Some(i)) if alt && i + 1 < len => {
+            app.footer_items.swap(i, i + 1);
+            app.footer_selected = Some(i + 1);
+        }
+        (KeyCode::Up | KeyCode::Down, _) if alt => {}
+        (KeyCode::Up, None) if len > 0 => app.footer_selected = Some(len - 1),
+        (KeyCode::Up, Some(i)) => app.footer_selected = Some(i.saturating_sub(1)),
+        (KeyCode::Down, Some(i)) => app.footer_selected = (i + 1 < len).then_some(i + 1),
+        (KeyCode::Char('d'), _) if alt => app.duplicate_footer_line(),
+        (KeyCode::Char('d'), Some(i)) if ctrl => {
+            app.footer_items.remove(i);
+            app.footer_selected = i.checked_sub(1).or((len > 1).then_some(0));
+        }
+        _ => return false,
+    }
+    true
+}
+
+/// Keys in compact mode; returns how the session ends, if it does.
+fn handle_compact_key(app: &mut App, key: KeyEvent) -> Option<Outcome> {
+    if key.code == KeyCode::Tab {
+        complete_header(app);
+        return None;
+    }
+    app.completion = None;
+    let confirm = key.code == KeyCode::Enter || app.config.confirm_key.matches(&key);
+    let input = app.compact.as_mut()?;
+    match key.code {
+        _ if confirm => {
+            let Some(parsed) = parse_header(&input.value, ScopeFormat::Parens) else {
+                app.issue = Some(Issue::error(
+                    Focus::Description,
+                    "expected `type(scope)!: description`",
+                ));
+                return None;
+            };
+            if let Err(message) = app.apply_parsed(&parsed) {
+                app.issue = Some(Issue::error(Focus::Type, message));
+                return None;
+            }
+            match first_error(validate(app)) {
+                Some(issue) => app.issue = Some(issue),
+                None => return Some(Outcome::Written),
+            }
+        }
+        KeyCode::Esc => return Some(Outcome::Aborted),
+        KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+            return Some(Outcome::Aborted);
+        }
+        KeyCode::Left => input.move_left(),
+        KeyCode::Right => input.move_right(),
+        KeyCode::Home => input.move_home(),
+        KeyCode::End => input.move_end(),
+        KeyCode::Delete => input.delete(),
+        KeyCode::Backspace => input.backspace(),
+        KeyCode::Char(c) => input.insert_char(c),
+        _ => {}
+    }
+    None
+}
+
+/// What Tab completes from, so repeated presses cycle through the matches.
+struct Completion {
+    /// The segment as typed before the first Tab.
+    prefix: String,
+    /// The match shown last.
+    index: usize,
+}
+
+/// Tab in compact mode: complete the type or scope at the end of the header
+/// from the known ones. A unique match is filled in; with several, each Tab
+/// shows the next and the status line lists them all.
+fn complete_header(app: &mut App) {
+    let Some(input) = &app.compact else {
+        return;
+    };
+    if input.cursor != input.value.len() {
+        return;
+    }
+    let text = input.value.clone();
+    let (what, typed, names): (&str, &str, Vec<String>) = match header_segment(&text) {
+        Some(HeaderSegment::Type(typed)) => (
+            "type",
+            typed,
+            app.types.iter().map(|t| t.name.clone()).collect(),
+        ),
+        Some(HeaderSegment::Scope(typed)) => (
+            "scope",
+            typed,
+            app.scopes.iter().map(|s| s.name.clone()).collect(),
+        ),
+        None => return,
+    };
+    let (prefix, index) = match app.completion.take() {
+        Some(last) => (last.prefix, last.index + 1),
+        None => (typed.to_string(), 0),
+    };
+    let matches: Vec<&String> = names.iter().filter(|n| n.starts_with(&prefix)).collect();
+    if matches.is_empty() {
+        app.notice = Some(format!("no {what} starts with `{prefix}`"));
+        return;
+    }
+    let index = index % matches.len();
+    let head = &text[..text.len() - typed.len()];
+    app.compact = Some(TextInput::from(format!("{head}{}", matches[index])));
+    if matches.len() > 1 {
+        let listed: Vec<&str> = matches.iter().map(|n| n.as_str()).collect();
+        app.notice = Some(format!("{} (Tab for the next)", listed.join(" · ")));
+        app.completion = Some(Completion { prefix, index });
+    }
+}
+
+/// Rows reserved for the form when rendering inline instead of full screen.
+const INLINE_HEIGHT: u16 = 24;
+
+type Tui = Terminal<CrosstermBackend<Stdout>>;
+
+/// What the form starts from, as handed over by Git.
+enum Prefill {
+    /// A message already given to Git (e.g. via `-m`), split into the fields
+    Message(String),
+    /// A commit template (`-t` or `commit.template`), used as the body
+    Template(String),
+}
+
+/// `prefill` is what Git prepared for the message; `amending` the hash of the
+/// commit `--amend` rewrites. Without `intent`, a fresh message starts with the
+/// intent picker, if any are configured.
+fn run_tui(
+    hook_path: PathBuf,
+    config: Config,
+    prefill: Option<Prefill>,
+    amending: Option<String>,
+    intent: Option<String>,
+) -> Result<Outcome> {
+    let alternate_screen = config.alternate_screen;
+    // built before the screen is taken over so load warnings stay visible
+    let mut app = App::new(config, hook_path);
+    // a template only scaffolds the body, so the message is still new
+    let fresh = !matches!(prefill, Some(Prefill::Message(_)));
+    match prefill {
+        Some(Prefill::Message(raw)) => app.prefill_from_raw(&raw),
+        Some(Prefill::Template(raw)) => app.prefill_body_from_template(&raw),
+        None => {}
+    }
+    app.amending = amending;
+    match intent {
+        Some(name) => app.apply_intent(&name).map_err(anyhow::Error::msg)?,
+        None if fresh
+            && app.compact.is_none()
+            && !app.config.freeform
+            && !app.config.intents.is_empty() =>
+        {
+            app.overlay = Some(Overlay {
+                target: OverlayTarget::PickIntent {
+                    selected: 0,
+                    names: app.config.intents.keys().cloned().collect(),
+                },
+                input: TextInput::new(),
+            });
+        }
+        None => {}
+    }
+    // without a terminal to draw on, ask plain questions instead
+    let outcome = if io::stdout().is_terminal() && enable_raw_mode().is_ok() {
+        run_terminal(&mut app, alternate_screen)?
+    } else {
+        prompt::run(&mut app)?
+    };
+    if outcome != Outcome::Written {
+        // leave Git's message file (and the draft) as they are
+        return Ok(outcome);
+    }
+
+    // write out the commit message
+    let msg = with_trailing_newline(&app.commit_message(), app.config.trailing_newline);
+    let msg = git::encode_message(&msg);
+    fs::write(&app.hook_path, msg).with_context(|| {
+        format!(
+            "failed to write commit message to `{}`",
+            app.hook_path.display()
         )
     })?;
+    Draft::discard()?;
+    if let Some(mut scope) = app.scope_to_save(Outcome::Written) {
+        persist_new_scope(&mut scope, None)?;
+    }
+    let scope = app.normalized_scope();
+    if !scope.is_empty() {
+        record_scope_use(&scope)?;
+    }
+    if app.config.summary {
+        println!("{}", summary(&app));
+    }
+
+    Ok(Outcome::Written)
+}
+
+/// One line about the written message for the scrollback, e.g.
+/// `pre-form: feat(api) · header 24/72 chars · 3 body lines · breaking`.
+fn summary(app: &App) -> String {
+    let parts = app.message_parts();
+    let mut context = parts.commit_type.clone();
+    if !parts.scope.is_empty() {
+        context.push_str(&format!("({})", parts.scope));
+    }
+    let message = app.commit_message();
+    let header = message.lines().next().unwrap_or("").chars().count();
+    let header = match app.config.header_max_length {
+        Some(max) => format!("header {header}/{max} chars"),
+        None => format!("header {header} chars"),
+    };
+    let body_lines = parts.body.lines().count();
+    let body_lines = format!(
+        "{body_lines} body line{}",
+        if body_lines == 1 { "" } else { "s" }
+    );
+    if app.config.freeform {
+        return format!("pre-form: {header} · {body_lines}");
+    }
+    let mut summary = format!("pre-form: {context} · {header} · {body_lines}");
+    if app.breaking {
+        summary.push_str(" · breaking");
+    }
+    summary
+}
+
+/// The TUI itself, on a terminal already in raw mode.
+fn run_terminal(app: &mut App, alternate_screen: bool) -> Result<Outcome> {
+    // compact mode has no room for the draft prompt
+    if let Some(draft) = Draft::load().filter(|_| app.compact.is_none()) {
+        app.overlay = Some(Overlay {
+            target: OverlayTarget::RestoreDraft(draft),
+            input: TextInput::new(),
+        });
+    }
+
+    let mut stdout = io::stdout();
+    let cursor = app.config.theme.cursor;
+    enter_terminal(&mut stdout, alternate_screen, cursor)?;
+
+    let backend = CrosstermBackend::new(stdout);
+    let viewport = if alternate_screen {
+        Viewport::Fullscreen
+    } else {
+        Viewport::Inline(INLINE_HEIGHT)
+    };
+    let mut terminal = Terminal::with_options(backend, TerminalOptions { viewport })
+        .context("failed to initialize TUI terminal")?;
+
+    let outcome = event_loop(&mut terminal, app);
+    // restore the terminal even when the loop bailed out with an error
+    restore_terminal(&mut terminal, alternate_screen, cursor.is_some())?;
+    outcome
+}
+
+/// Screen and cursor setup, once raw mode is on; undone by `restore_terminal`.
+fn enter_terminal(
+    out: &mut impl io::Write,
+    alternate_screen: bool,
+    cursor: Option<CursorShape>,
+) -> Result<()> {
+    if let Some(shape) = cursor {
+        execute!(out, shape.style()).context("failed to set cursor style")?;
+    }
+    if alternate_screen {
+        execute!(out, EnterAlternateScreen, EnableMouseCapture)
+            .context("failed to enter alternate screen / enable mouse capture")?;
+    } else {
+        execute!(out, EnableMouseCapture).context("failed to enable mouse capture")?;
+    }
+    Ok(())
+}
 
+/// Hand the terminal to `preview_cmd` with the message on its stdin, then take
+/// it back. A failing command is reported in the status line.
+fn preview_message(terminal: &mut Tui, app: &mut App) -> Result<()> {
+    let Some(command) = app.config.preview_cmd.clone() else {
+        app.notice = Some("set preview_cmd in the config to preview the message".to_string());
+        return Ok(());
+    };
+    let alternate_screen = app.config.alternate_screen;
+    let cursor = app.config.theme.cursor;
+    restore_terminal(terminal, alternate_screen, cursor.is_some())?;
+    let previewed = run_preview(&command, &app.commit_message());
+    enable_raw_mode().context("failed to enable raw mode")?;
+    if previewed.is_ok() {
+        // keep the output on screen until the user is done reading it
+        print!("\r\n[press any key to return]");
+        io::stdout()
+            .flush()
+            .context("failed to write to terminal")?;
+        while !matches!(
+            event::read().context("failed to read terminal event")?,
+            Event::Key(_)
+        ) {}
+    }
+    enter_terminal(terminal.backend_mut(), alternate_screen, cursor)?;
+    terminal.clear().context("failed to redraw TUI")?;
+    if let Err(e) = previewed {
+        app.notice = Some(format!("preview failed: {e:#}"));
+    }
     Ok(())
 }
 
-pub fn install_hook() -> Result<()> {
-    let hook_dir = Path::new(".git/hooks");
-    fs::create_dir_all(hook_dir)
-        .with_context(|| format!("failed to create directory `{}`", hook_dir.display()))?;
-
-    let hook_path = hook_dir.join("prepare-commit-msg");
-    let script = r#"#!/bin/sh
-# pre-form Git hook: generates commit message via TUI
-if [ -z "$2" ]; then
-  pre-form "$1"
-fi
-"#;
-
-    let mut file = File::create(&hook_path)
-        .with_context(|| format!("failed to create hook file `{}`", hook_path.display()))?;
-    file.write_all(script.as_bytes())
-        .with_context(|| format!("failed to write to `{}`", hook_path.display()))?;
-    fs::set_permissions(&hook_path, fs::Permissions::from_mode(0o755))
-        .with_context(|| format!("failed to set permissions on `{}`", hook_path.display()))?;
-    println!("Git hook installed successfully at {}", hook_path.display());
+/// Run `command` through the shell with `message` on its stdin.
+fn run_preview(command: &str, message: &str) -> Result<()> {
+    let mut child = std::process::Command::new("sh")
+        .args(["-c", command])
+        .stdin(Stdio::piped())
+        .spawn()
+        .context("could not start sh")?;
+    if let Some(mut stdin) = child.stdin.take() {
+        // a viewer may quit without reading everything
+        let _ = stdin.write_all(message.as_bytes());
+    }
+    let status = child.wait().context("preview command did not finish")?;
+    match status.code() {
+        Some(0) => Ok(()),
+        Some(127) => anyhow::bail!("`{command}` not found"),
+        _ => anyhow::bail!("`{command}` exited with {status}"),
+    }
+}
+
+fn event_loop(terminal: &mut Tui, app: &mut App) -> Result<Outcome> {
+    let mut autosave = Autosave::new(app);
+    let outcome = handle_events(terminal, app, &mut autosave)?;
+    // a written message discards the draft, anything else keeps the last edits
+    if outcome != Outcome::Written {
+        autosave.flush(app)?;
+    }
+    Ok(outcome)
+}
+
+fn handle_events(terminal: &mut Tui, app: &mut App, autosave: &mut Autosave) -> Result<Outcome> {
+    loop {
+        terminal
+            .draw(|f| draw_ui(f, app))
+            .context("failed to draw TUI frame")?;
+        autosave.tick(app)?;
+
+        if !event::poll(Duration::from_millis(200)).context("failed to poll for terminal events")? {
+            continue;
+        }
+        let Event::Key(key) = event::read().context("failed to read terminal event")? else {
+            continue;
+        };
+        autosave.touch();
+        app.issue = None;
+        app.notice = None;
+
+        if app.compact.is_some() {
+            if let Some(outcome) = handle_compact_key(app, key) {
+                return Ok(outcome);
+            }
+            continue;
+        }
+
+        // The log preview only closes.
+        if let Some(Overlay {
+            target: OverlayTarget::LogPreview { .. },
+            ..
+        }) = app.overlay
+        {
+            let ctrl_g =
+                key.modifiers.contains(KeyModifiers::CONTROL) && key.code == KeyCode::Char('g');
+            if ctrl_g || matches!(key.code, KeyCode::Esc | KeyCode::Char('q')) {
+                app.overlay = None;
+            }
+            continue;
+        }
+
+        // The help only scrolls and closes.
+        if let Some(Overlay {
+            target: OverlayTarget::Help { scroll },
+            ..
+        }) = app.overlay
+        {
+            let size = terminal.size().context("failed to read terminal size")?;
+            let popup = help_rect(app, Rect::new(0, 0, size.width, size.height));
+            let page = popup.height.saturating_sub(2) as usize;
+            let max = help_lines(app).len().saturating_sub(page);
+            let scroll = match key.code {
+                KeyCode::Esc | KeyCode::Char('?' | 'q') | KeyCode::F(1) => {
+                    app.overlay = None;
+                    continue;
+                }
+                KeyCode::Up => scroll.saturating_sub(1),
+                KeyCode::Down => scroll + 1,
+                KeyCode::PageUp => scroll.saturating_sub(page),
+                KeyCode::PageDown => scroll + page,
+                KeyCode::Home => 0,
+                KeyCode::End => max,
+                _ => scroll,
+            };
+            app.overlay = Some(Overlay {
+                target: OverlayTarget::Help {
+                    scroll: scroll.min(max),
+                },
+                input: TextInput::new(),
+            });
+            continue;
+        }
+
+        // The reset prompt only answers yes or no.
+        if let Some(Overlay {
+            target: OverlayTarget::ConfirmReset,
+            ..
+        }) = app.overlay
+        {
+            match key.code {
+                KeyCode::Char('y') | KeyCode::Enter => {
+                    app.overlay = None;
+                    app.reset();
+                }
+                KeyCode::Char('n') | KeyCode::Esc => app.overlay = None,
+                _ => {}
+            }
+            continue;
+        }
+
+        // The draft prompt only answers yes or no.
+        if let Some(Overlay {
+            target: OverlayTarget::RestoreDraft(_),
+            ..
+        }) = app.overlay
+        {
+            match key.code {
+                KeyCode::Char('y') | KeyCode::Enter => {
+                    if let Some(Overlay {
+                        target: OverlayTarget::RestoreDraft(draft),
+                        ..
+                    }) = app.overlay.take()
+                    {
+                        draft.apply(app);
+                    }
+                }
+                KeyCode::Char('n') | KeyCode::Esc => {
+                    app.overlay = None;
+                    Draft::discard()?;
+                }
+                _ => {}
+            }
+            continue;
+        }
+
+        if handle_picker_key(app, key)? {
+            continue;
+        }
+
+        // If an overlay is open, handle it first and continue.
+        if let Some(ov) = &mut app.overlay {
+            match key.code {
+                KeyCode::Esc => {
+                    app.overlay = None;
+                }
+                KeyCode::Enter => {
+                    let name = ov.input.value.trim();
+                    match ov.target {
+                        OverlayTarget::NewType if !name.is_empty() => {
+                            if !is_valid_type_name(name) {
+                                // keep the overlay open so the name can be fixed
+                                continue;
+                            }
+                            persist_new_type(name)?;
+                            app.types
+                                .push(CommitType::new(name.to_string(), &app.config));
+                            group_types(&mut app.types);
+                            app.type_idx =
+                                app.types.iter().position(|t| t.name == name).unwrap_or(0);
+                        }
+                        OverlayTarget::NewScope if !name.is_empty() => {
+                            let mut scope = Scope {
+                                name: name.to_string(),
+                                description: None,
+                                uses: 0,
+                                detected: false,
+                            };
+                            persist_new_scope(&mut scope, app.config.scope_case)?;
+                            app.scope = TextInput::from(scope.name.clone());
+                            app.scopes.push(scope);
+                            app.advance_to(Focus::Description); // move on
+                        }
+                        OverlayTarget::SaveDraft if !name.is_empty() => {
+                            if !draft::is_valid_draft_name(name) {
+                                // keep the overlay open so the name can be fixed
+                                continue;
+                            }
+                            let name = name.to_string();
+                            Draft::capture(app).save_named(&name)?;
+                            app.notice = Some(format!("saved draft `{name}`"));
+                        }
+                        // an empty description is allowed and clears it
+                        OverlayTarget::EditTypeDescription => {
+                            let description = name.to_string();
+                            let selected = &mut app.types[app.type_idx];
+                            persist_type_description(&selected.name, &description)?;
+                            selected.description = (!description.is_empty()).then_some(description);
+                        }
+                        _ => {}
+                    }
+                    app.overlay = None;
+                }
+                KeyCode::Left => ov.input.move_left(),
+                KeyCode::Right => ov.input.move_right(),
+                KeyCode::Home => ov.input.move_home(),
+                KeyCode::End => ov.input.move_end(),
+                KeyCode::Delete => ov.input.delete(),
+                KeyCode::Backspace => ov.input.backspace(),
+                KeyCode::Char(c) => ov.input.insert_char(c),
+                _ => {}
+            }
+            continue;
+        }
+
+        // snippets go into whichever text field has focus
+        if let Some(snippet) = app
+            .config
+            .snippets
+            .values()
+            .find(|s| s.key.matches(&key))
+            .map(|s| s.text.clone())
+        {
+            if let Some(t) = current_input_mut(app) {
+                t.insert_snippet(&snippet);
+            }
+            continue;
+        }
+
+        // global bindings, available from every field
+        if app.config.confirm_key.matches(&key) {
+            if confirm(app) {
+                return Ok(Outcome::Written);
+            }
+            continue;
+        }
+        if let Some(action) = global_action(&key) {
+            match action {
+                Action::Abort => return Ok(Outcome::Aborted),
+                Action::Copy => app.copy_message(),
+                Action::Preview => preview_message(terminal, app)?,
+                Action::QuickSwitch => {
+                    app.overlay = Some(Overlay {
+                        target: OverlayTarget::QuickSwitch { selected: 0 },
+                        input: TextInput::new(),
+                    });
+                }
+                Action::ReuseLast => app.reuse_last_context(),
+                Action::LogPreview => match git::author_ident() {
+                    Some((author, date)) => {
+                        app.overlay = Some(Overlay {
+                            target: OverlayTarget::LogPreview {
+                                author,
+                                date,
+                                branch: git::current_branch(),
+                            },
+                            input: TextInput::new(),
+                        });
+                    }
+                    None => app.notice = Some("git has no author identity set".to_string()),
+                },
+                Action::AutomationTag => app.cycle_automation_tag(),
+                Action::Reset => {
+                    if app.has_content() {
+                        app.overlay = Some(Overlay {
+                            target: OverlayTarget::ConfirmReset,
+                            input: TextInput::new(),
+                        });
+                    } else {
+                        app.reset();
+                    }
+                }
+                Action::SaveDraft => {
+                    app.overlay = Some(Overlay {
+                        target: OverlayTarget::SaveDraft,
+                        input: TextInput::new(),
+                    });
+                }
+                Action::LoadDraft => {
+                    let names = draft::list_named();
+                    if names.is_empty() {
+                        app.notice = Some("no saved drafts".to_string());
+                    } else {
+                        app.overlay = Some(Overlay {
+                            target: OverlayTarget::PickDraft { selected: 0, names },
+                            input: TextInput::new(),
+                        });
+                    }
+                }
+                Action::NormalizeScope => app.scope = TextInput::from(app.normalized_scope()),
+                Action::ToggleBreaking => app.breaking = !app.breaking,
+                Action::ToggleBodyPreview => app.body_preview = !app.body_preview,
+                Action::Help => open_help(app),
+            }
+            continue;
+        }
+        if app.config.accept_suggestion_key.matches(&key)
+            && let Some(rest) = app.scope_suggestion()
+        {
+            let scope = format!("{}{rest}", app.scope.value);
+            app.scope = TextInput::from(scope);
+            continue;
+        }
+        // Alt+1.. jumps to a field by the number in its title
+        if key.modifiers.contains(KeyModifiers::ALT)
+            && let KeyCode::Char(c @ '1'..='9') = key.code
+        {
+            let n = c as usize - '1' as usize;
+            if let Some(focus) = app.field_order().get(n) {
+                app.focus = *focus;
+            }
+            continue;
+        }
+        if app.focus == Focus::Footer && handle_footer_key(app, key) {
+            continue;
+        }
+        // a hard line break; the header fields stay on one line
+        if key.modifiers.contains(KeyModifiers::ALT) && key.code == KeyCode::Enter {
+            if app.focus == Focus::Body {
+                app.body.insert_char('\n');
+            }
+            continue;
+        }
+
+        match key.code {
+            KeyCode::Tab => app.focus = app.next_focus(),
+            KeyCode::PageUp => app.hunk_scroll = app.hunk_scroll.saturating_sub(HUNK_PAGE),
+            KeyCode::PageDown => {
+                let last = app.hunks.len().saturating_sub(1);
+                app.hunk_scroll = (app.hunk_scroll + HUNK_PAGE).min(last);
+            }
+            KeyCode::Up if app.focus == Focus::Type && app.type_idx > 0 => {
+                app.select_type(app.type_idx - 1);
+            }
+            KeyCode::Down if app.focus == Focus::Scope && !app.scopes.is_empty() => {
+                app.overlay = Some(Overlay {
+                    target: OverlayTarget::PickScope { selected: 0 },
+                    input: TextInput::new(),
+                });
+            }
+            KeyCode::Down if app.focus == Focus::Type && app.type_idx + 1 < app.types.len() => {
+                app.select_type(app.type_idx + 1);
+            }
+            // Enter would write the message, so picking a type uses Space
+            KeyCode::Char(' ') if app.focus == Focus::Type && app.config.auto_advance => {
+                app.advance_to(app.next_focus());
+            }
+
+            // text editing in inputs
+            KeyCode::Left => {
+                if let Some(t) = current_input_mut(app) {
+                    t.move_left();
+                }
+            }
+            KeyCode::Right => {
+                if let Some(t) = current_input_mut(app) {
+                    t.move_right();
+                }
+            }
+            KeyCode::Home => {
+                if let Some(t) = current_input_mut(app) {
+                    t.move_home();
+                }
+            }
+            KeyCode::End => {
+                if let Some(t) = current_input_mut(app) {
+                    t.move_end();
+                }
+            }
+            KeyCode::Delete => {
+                if let Some(t) = current_input_mut(app) {
+                    t.delete();
+                }
+            }
+            KeyCode::Backspace => {
+                if let Some(t) = current_input_mut(app) {
+                    t.backspace();
+                }
+            }
+
+            // open modal to add type/scope
+            KeyCode::Char('+') => maybe_open_overlay(app),
+
+            KeyCode::Char('?') if app.focus == Focus::Type => open_help(app),
+            KeyCode::Char('/') if app.focus == Focus::Type => {
+                app.overlay = Some(Overlay {
+                    target: OverlayTarget::FilterTypes {
+                        selected: app.type_idx,
+                    },
+                    input: TextInput::new(),
+                });
+            }
+            KeyCode::Char('/')
+                if app.focus == Focus::Scope
+                    && app.scope.value.is_empty()
+                    && !app.scopes.is_empty() =>
+            {
+                app.overlay = Some(Overlay {
+                    target: OverlayTarget::PickScope { selected: 0 },
+                    input: TextInput::new(),
+                });
+            }
+
+            KeyCode::Char('e') if app.focus == Focus::Type => {
+                let current = app.types[app.type_idx].description.clone();
+                app.overlay = Some(Overlay {
+                    target: OverlayTarget::EditTypeDescription,
+                    input: TextInput::from(current.unwrap_or_default()),
+                });
+            }
+
+            KeyCode::Char(c) => match app.focus {
+                Focus::Scope | Focus::Description | Focus::Ticket | Focus::Body | Focus::Footer => {
+                    // a hard limit refuses the character and rings the bell
+                    if let Some((input, per_line, limit)) = app.length_limit(app.focus)
+                        && limit.hard
+                        && !text::room_for_char(&input.value, input.cursor, per_line, limit.max)
+                    {
+                        app.notice = Some(format!("limited to {} characters", limit.max));
+                        execute!(terminal.backend_mut(), Print('\x07'))
+                            .context("failed to ring the bell")?;
+                    } else if let Some(t) = current_input_mut(app) {
+                        t.insert_char(c);
+                    }
+                }
+                _ => {}
+            },
+
+            // Enter only writes from the last field, unless `enter_writes`
+            KeyCode::Enter if !app.config.enter_writes && app.focus == Focus::Body => {
+                app.body.insert_char('\n');
+            }
+            KeyCode::Enter
+                if !app.config.enter_writes && app.field_order().last() != Some(&app.focus) =>
+            {
+                app.advance_to(app.next_focus());
+            }
+            // finish, unless a rule blocks the message
+            KeyCode::Enter if confirm(app) => return Ok(Outcome::Written),
+            KeyCode::Esc => return Ok(Outcome::Aborted),
+            _ => {}
+        }
+    }
+}
+
+/// Whether the form may be written now. A blocking rule moves the focus to its
+/// field instead; a message that won't parse back as entered is only written
+/// when confirmed a second time.
+fn confirm(app: &mut App) -> bool {
+    app.move_description_ticket();
+    if let Some(issue) = first_error(validate(app)) {
+        app.focus = issue.field;
+        app.issue = Some(issue);
+        app.show_issues = true;
+        return false;
+    }
+    // warn once about a message that other tools would read differently. A
+    // custom header isn't meant to parse as conventional.
+    let message = app.commit_message();
+    let conventional = app.config.header_template.is_none()
+        && app.config.commit_template.is_none()
+        && !app.config.freeform;
+    let difference = if conventional {
+        // a footer tag such as `[skip ci]` isn't a trailer, so it is left out
+        // of the comparison rather than reported as a body/footer mix-up
+        let tag = match app.config.automation_tag_placement {
+            TagPlacement::Footer => app.automation_tag.take(),
+            TagPlacement::Header => None,
+        };
+        let difference = round_trip_difference(
+            &app.message_parts(),
+            &app.commit_message(),
+            app.config.scope_format,
+        );
+        app.automation_tag = app.automation_tag.or(tag);
+        difference
+    } else {
+        None
+    };
+    match difference {
+        Some(what) if app.round_trip_warned.as_ref() != Some(&message) => {
+            app.notice = Some(format!(
+                "{what} won't parse back as entered; confirm again to write anyway"
+            ));
+            app.round_trip_warned = Some(message);
+            false
+        }
+        _ => true,
+    }
+}
+
+fn restore_terminal(terminal: &mut Tui, alternate_screen: bool, reset_cursor: bool) -> Result<()> {
+    disable_raw_mode().context("failed to disable raw mode")?;
+    if reset_cursor {
+        execute!(terminal.backend_mut(), SetCursorStyle::DefaultUserShape)
+            .context("failed to reset cursor style")?;
+    }
+    if alternate_screen {
+        execute!(
+            terminal.backend_mut(),
+            LeaveAlternateScreen,
+            DisableMouseCapture
+        )
+        .context("failed to leave alternate screen / disable mouse capture")?;
+    } else {
+        // wipe the inline form so Git's output follows the earlier scrollback
+        terminal
+            .clear()
+            .context("failed to clear inline viewport")?;
+        execute!(terminal.backend_mut(), DisableMouseCapture)
+            .context("failed to disable mouse capture")?;
+    }
+    terminal
+        .show_cursor()
+        .context("failed to show terminal cursor")?;
     Ok(())
 }
 
-fn main() -> Result<()> {
+/// One scope per line, with its description after a tab when it has one.
+fn print_scopes(scopes: &[Scope]) {
+    for scope in scopes {
+        match &scope.description {
+            Some(description) => println!("{}\t{description}", scope.name),
+            None => println!("{}", scope.name),
+        }
+    }
+}
+
+/// Whether this repo turned pre-form off, with a `.pre-form-git/disabled`
+/// marker or `enabled = false`; the message is then left as Git prepared it.
+fn opted_out() -> Result<bool> {
+    if preform_dir().join("disabled").exists() {
+        return Ok(true);
+    }
+    Ok(!Config::load().context("failed to load config")?.enabled)
+}
+
+/// Amending replaces the commit, so its signature is lost unless re-signed.
+fn warn_if_amending_signed(sha: Option<&str>) {
+    let sha = sha.unwrap_or("HEAD");
+    if !git::commit_is_signed(sha) {
+        return;
+    }
+    if !git::signing_enabled() {
+        eprintln!(
+            "pre-form: warning: amending signed commit {sha}, but commit.gpgsign is off; \
+             the amended commit will be unsigned (use `git commit --amend -S`)"
+        );
+    } else {
+        eprintln!("pre-form: note: amending signed commit {sha}; it will be re-signed");
+    }
+}
+
+fn main() -> Result<ExitCode> {
     let args = Args::parse();
-    match args.command {
-        Some(Command::Install) => {
-            install_hook().context("failed to install git hook")?;
+    // a repo can opt out of a globally installed hook
+    if !matches!(
+        args.command,
+        Some(
+            Command::Install { .. }
+                | Command::CheckHook { .. }
+                | Command::Scopes { .. }
+                | Command::Types { .. }
+                | Command::Draft { .. }
+        )
+    ) && opted_out()?
+    {
+        return Ok(Outcome::Written.into());
+    }
+    let outcome = match args.command {
+        Some(Command::Install { hook, commit_msg }) => {
+            for (name, script) in HookChoice::resolve(hook, commit_msg).hooks() {
+                let path = install_hook(name, script).context("failed to install git hook")?;
+                println!("Git hook installed successfully at {}", path.display());
+            }
+            Outcome::Written
+        }
+        Some(Command::CheckHook { hook, commit_msg }) => {
+            let choice = HookChoice::resolve(hook, commit_msg);
+            let install = choice.install_command();
+            for (name, script) in choice.hooks() {
+                let (status, path) =
+                    check_hook(name, script).context("failed to check git hook")?;
+                let path = path.display();
+                match status {
+                    HookStatus::Installed => println!("{name} hook is installed at {path}"),
+                    HookStatus::Missing => {
+                        bail!("no {name} hook at {path}; run `{install}`")
+                    }
+                    HookStatus::NotExecutable => {
+                        bail!("{name} hook at {path} is not executable; run `{install}`")
+                    }
+                    HookStatus::Modified => bail!(
+                        "{name} hook at {path} differs from pre-form's; run `{install}` to replace it"
+                    ),
+                }
+            }
+            Outcome::Written
+        }
+        Some(Command::Validate { file, format }) => {
+            let file = file.filter(|f| f.as_os_str() != "-");
+            let raw = match &file {
+                Some(file) => fs::read_to_string(file).with_context(|| {
+                    format!("failed to read commit message `{}`", file.display())
+                })?,
+                None => io::read_to_string(io::stdin()).context("failed to read stdin")?,
+            };
+            let name = file
+                .as_ref()
+                .map_or_else(|| "<stdin>".to_string(), |f| f.display().to_string());
+            let config = Config::load().context("failed to load config")?;
+            let types = load_types(&config).0;
+            let mut app = App::offline(config, file.unwrap_or_default(), types);
+            let violations = validate_message(&raw, &mut app);
+            match format {
+                ReportFormat::Text => {
+                    for v in &violations {
+                        let level = match v.severity {
+                            Severity::Warn => "warning: ",
+                            _ => "",
+                        };
+                        eprintln!("{name}:{}:{}: {level}{}", v.line, v.column, v.message);
+                    }
+                }
+                ReportFormat::Json => println!(
+                    "{}",
+                    serde_json::to_string(&violations).context("failed to encode report")?
+                ),
+            }
+            if violations.iter().any(|v| v.severity == Severity::Error) {
+                Outcome::Invalid
+            } else {
+                Outcome::Written
+            }
+        }
+        Some(Command::Draft { action }) => {
+            match action {
+                DraftAction::Save { name } => {
+                    let pending = Draft::load()
+                        .context("no unfinished draft to save; press Ctrl+S in the form instead")?;
+                    pending.save_named(&name)?;
+                    Draft::discard()?;
+                    println!("saved draft `{name}`");
+                }
+                DraftAction::Load { name } => {
+                    Draft::load_named(&name)?.save()?;
+                    println!("the next commit will offer to restore draft `{name}`");
+                }
+                DraftAction::List => {
+                    for name in draft::list_named() {
+                        println!("{name}");
+                    }
+                }
+            }
+            Outcome::Written
+        }
+        Some(Command::Scopes { action }) => {
+            match action {
+                ScopesAction::List => {}
+                ScopesAction::Add { name, description } => {
+                    let config = Config::load().context("failed to load config")?;
+                    let mut scope = Scope {
+                        name,
+                        description,
+                        uses: 0,
+                        detected: false,
+                    };
+                    if let Some(case) = config.scope_case {
+                        scope.name = scopes::normalize_scope(&scope.name, case);
+                    }
+                    if scope.name.trim().is_empty() {
+                        bail!("a scope needs a name");
+                    }
+                    if load_scopes().iter().any(|s| s.name == scope.name) {
+                        eprintln!("scope `{}` is already known", scope.name);
+                    } else {
+                        persist_new_scope(&mut scope, None).context("failed to add scope")?;
+                    }
+                }
+                ScopesAction::Remove { name } => {
+                    if !remove_scope(&name).context("failed to remove scope")? {
+                        eprintln!("no scope named `{name}`; nothing removed");
+                    }
+                }
+            }
+            print_scopes(&load_scopes());
+            Outcome::Written
+        }
+        Some(Command::Types { action }) => {
+            match action {
+                TypesAction::List => {}
+                TypesAction::Add { name } => {
+                    if !is_valid_type_name(&name) {
+                        bail!("invalid type `{name}`; type names must match [a-z0-9-]+");
+                    }
+                    if components_dir().join(&name).exists() {
+                        eprintln!("type `{name}` already exists");
+                    } else {
+                        persist_new_type(&name)?;
+                    }
+                }
+                TypesAction::Remove { name } => {
+                    if !remove_type(&name)? {
+                        eprintln!("no component file for type `{name}`; nothing removed");
+                    }
+                }
+            }
+            let config = Config::load().context("failed to load config")?;
+            for commit_type in load_types(&config).0 {
+                match commit_type.description {
+                    Some(description) => println!("{}\t{description}", commit_type.name),
+                    None => println!("{}", commit_type.name),
+                }
+            }
+            Outcome::Written
         }
         None => {
             // Accept path from git hook
-            let hook_path = env::args()
-                .nth(1)
-                .or(args.commit_msg_path)
+            let hook_path = args
+                .commit_msg_path
                 .context("no hook_path provided; expected path to hooks/prepare-commit-msg")?;
             let hook_path = PathBuf::from(hook_path);
-            run_tui(hook_path).context("failed while running TUI for commit message")?;
+            let mut config = Config::load().context("failed to load config")?;
+            if args.inline {
+                config.alternate_screen = false;
+            }
+            if args.compact {
+                config.compact = true;
+            }
+            if args.quiet {
+                config.summary = false;
+            }
+            if args.freeform {
+                config.freeform = true;
+            }
+            if args.preview_cmd.is_some() {
+                config.preview_cmd = args.preview_cmd;
+            }
+            match args.commit_source {
+                None => run_tui(hook_path, config, None, None, args.intent)
+                    .context("failed while running TUI for commit message")?,
+                // opt-in: turn a quick `-m` message into a structured one
+                Some(CommitSource::Message) if config.format_inline_messages => {
+                    let given = fs::read_to_string(&hook_path).with_context(|| {
+                        format!("failed to read commit message `{}`", hook_path.display())
+                    })?;
+                    let prefill = Prefill::Message(given);
+                    run_tui(hook_path, config, Some(prefill), None, args.intent)
+                        .context("failed while running TUI for commit message")?
+                }
+                // start from the template's text instead of overwriting it
+                Some(CommitSource::Template) => {
+                    let given = fs::read_to_string(&hook_path).with_context(|| {
+                        format!("failed to read commit template `{}`", hook_path.display())
+                    })?;
+                    let prefill = Prefill::Template(given);
+                    run_tui(hook_path, config, Some(prefill), None, args.intent)
+                        .context("failed while running TUI for commit message")?
+                }
+                // opt-in: edit an amended message in the form, starting from it
+                Some(CommitSource::Commit) if config.edit_amends => {
+                    if args.amend_safe {
+                        warn_if_amending_signed(args.commit_sha.as_deref());
+                    }
+                    let given = fs::read_to_string(&hook_path).with_context(|| {
+                        format!("failed to read commit message `{}`", hook_path.display())
+                    })?;
+                    // `--amend` passes HEAD; `-c`/`-C` name the commit whose
+                    // message is reused, which isn't being amended
+                    let amending = match args.commit_sha.as_deref() {
+                        None | Some("HEAD") => Some(
+                            git::git(&["rev-parse", "--short", "HEAD"])
+                                .unwrap_or_else(|| "HEAD".to_string()),
+                        ),
+                        Some(_) => None,
+                    };
+                    let prefill = Prefill::Message(given);
+                    run_tui(hook_path, config, Some(prefill), amending, args.intent)
+                        .context("failed while running TUI for commit message")?
+                }
+                // `-m` messages are final as given; leave the file untouched.
+                Some(CommitSource::Message) => Outcome::Written,
+                // merges, squashes and amends keep Git's prepared message
+                Some(source) => {
+                    if source == CommitSource::Commit && args.amend_safe {
+                        warn_if_amending_signed(args.commit_sha.as_deref());
+                    }
+                    Outcome::Written
+                }
+            }
+        }
+    };
+    Ok(outcome.into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ratatui::backend::TestBackend;
+    use ratatui::layout::Position;
+
+    /// A form with two types and nothing typed, independent of the
+    /// components in the working directory.
+    fn app() -> App {
+        let config = Config::default();
+        let mut app = App::new(config, PathBuf::new());
+        app.types = ["feat", "fix"]
+            .into_iter()
+            .map(|name| CommitType::new(name.to_string(), &app.config))
+            .collect();
+        app.type_idx = 0;
+        app.scopes.clear();
+        app.focus = Focus::Description;
+        app
+    }
+
+    /// Draw `app` on a `width`×`height` screen; the rows of the buffer and
+    /// where the cursor ended up.
+    fn render(app: &App, width: u16, height: u16) -> (Vec<String>, Position) {
+        let mut terminal = Terminal::new(TestBackend::new(width, height)).unwrap();
+        terminal.draw(|f| draw_ui(f, app)).unwrap();
+        let cursor = terminal.get_cursor_position().unwrap();
+        let buffer = terminal.backend().buffer();
+        let rows = (0..height)
+            .map(|y| {
+                (0..width)
+                    .map(|x| buffer[(x, y)].symbol())
+                    .collect::<String>()
+            })
+            .collect();
+        (rows, cursor)
+    }
+
+    fn row_containing(rows: &[String], text: &str) -> Option<usize> {
+        rows.iter().position(|row| row.contains(text))
+    }
+
+    #[test]
+    fn reset_in_freeform_focuses_a_visible_field() {
+        let mut app = app();
+        app.config.freeform = true;
+        app.reset();
+        assert!(app.field_enabled(app.focus));
+        assert_eq!(app.focus, app.field_order()[0]);
+    }
+
+    #[test]
+    fn flags_before_the_message_path_are_not_the_path() {
+        let args = Args::try_parse_from(["pre-form", "--quiet", "msg"]).unwrap();
+        assert!(args.quiet);
+        assert_eq!(args.commit_msg_path.as_deref(), Some("msg"));
+    }
+
+    #[test]
+    fn amend_safe_before_the_hook_arguments() {
+        let args =
+            Args::try_parse_from(["pre-form", "--amend-safe", "msg", "commit", "HEAD"]).unwrap();
+        assert!(args.amend_safe);
+        assert_eq!(args.commit_msg_path.as_deref(), Some("msg"));
+        assert_eq!(args.commit_source, Some(CommitSource::Commit));
+        assert_eq!(args.commit_sha.as_deref(), Some("HEAD"));
+    }
+
+    #[test]
+    fn base_form_shows_fields_and_cursor_in_focused_one() {
+        let mut app = app();
+        app.description = TextInput::from("add login".to_string());
+        let (rows, cursor) = render(&app, 100, 40);
+        for title in ["Type", "Scope", "Description", "Body", "Footer"] {
+            assert!(
+                row_containing(&rows, title).is_some(),
+                "no `{title}` in\n{}",
+                rows.join("\n")
+            );
         }
+        let typed = row_containing(&rows, "add login").expect("description drawn");
+        assert_eq!(cursor.y as usize, typed);
+        let start = rows[typed].find("add login").unwrap();
+        // one cell per char: borders are single-width
+        let column = rows[typed][..start].chars().count();
+        assert_eq!(cursor.x as usize, column + "add login".len());
+    }
+
+    #[test]
+    fn open_overlay_is_drawn_with_cursor_in_it() {
+        let mut app = app();
+        app.overlay = Some(Overlay {
+            target: OverlayTarget::NewScope,
+            input: TextInput::from("api".to_string()),
+        });
+        let (rows, cursor) = render(&app, 100, 40);
+        let title = row_containing(&rows, "New Scope").expect("overlay drawn");
+        assert!(rows[title + 1].contains("api"));
+        assert_eq!(cursor.y as usize, title + 1);
+    }
+
+    #[test]
+    fn help_lists_every_global_key() {
+        let mut app = app();
+        open_help(&mut app);
+        let (rows, _) = render(&app, 100, 40);
+        assert!(row_containing(&rows, "Keys").is_some());
+        for (spec, _, help) in GLOBAL_KEYS {
+            let key = global_key(spec).to_string();
+            let row = row_containing(&rows, help).unwrap_or_else(|| panic!("no `{help}`"));
+            assert!(rows[row].contains(&key), "`{help}` without {key}");
+        }
+    }
+
+    #[test]
+    fn tab_completes_and_cycles_compact_types() {
+        let mut app = app();
+        app.compact = Some(TextInput::from("fi".to_string()));
+        complete_header(&mut app);
+        assert_eq!(app.compact.as_ref().unwrap().value, "fix");
+        assert!(app.completion.is_none());
+
+        app.compact = Some(TextInput::from("f".to_string()));
+        let shown: Vec<String> = (0..3)
+            .map(|_| {
+                complete_header(&mut app);
+                app.compact.as_ref().unwrap().value.clone()
+            })
+            .collect();
+        assert_eq!(shown, ["feat", "fix", "feat"]);
+    }
+
+    #[test]
+    fn typed_scope_is_saved_only_when_written() {
+        let mut app = app();
+        app.scope = TextInput::from("parser".to_string());
+        let saved = app.scope_to_save(Outcome::Written).map(|s| s.name);
+        assert_eq!(saved.as_deref(), Some("parser"));
+        assert!(app.scope_to_save(Outcome::Aborted).is_none());
+        assert!(app.scope_to_save(Outcome::Invalid).is_none());
+    }
+
+    #[test]
+    fn known_scope_is_not_saved_again() {
+        let mut app = app();
+        app.scopes = scopes::parse_scopes("parser\n");
+        app.scope = TextInput::from("parser".to_string());
+        assert!(app.scope_to_save(Outcome::Written).is_none());
+        app.scope = TextInput::new();
+        assert!(app.scope_to_save(Outcome::Written).is_none());
+    }
+
+    #[test]
+    fn scope_suggestion_completes_a_typed_prefix_at_the_end() {
+        let mut app = app();
+        app.scopes = scopes::parse_scopes("parser\npanel\n");
+        app.focus = Focus::Scope;
+        app.scope = TextInput::from("pan".to_string());
+        assert_eq!(app.scope_suggestion(), Some("el"));
+        app.scope.move_left();
+        assert_eq!(app.scope_suggestion(), None);
+        app.scope = TextInput::from("panel".to_string());
+        assert_eq!(app.scope_suggestion(), None);
+    }
+
+    #[test]
+    fn narrow_terminal_keeps_cursor_on_screen() {
+        let mut app = app();
+        app.description = TextInput::from("x".repeat(200));
+        let (_, cursor) = render(&app, 40, 20);
+        assert!(cursor.x < 40 && cursor.y < 20, "cursor at {cursor:?}");
     }
-    Ok(())
 }
@@ -0,0 +1,141 @@
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::{Line, Span, Text};
+
+/// A rough rendering of markdown `text`: headings, lists, quotes and code
+/// blocks by line, bold, italic and code spans inline. Anything else is shown
+/// as typed.
+pub fn render(text: &str) -> Text<'static> {
+    let code = Style::default().fg(Color::Yellow);
+    let mut lines = Vec::new();
+    let mut in_fence = false;
+    for line in text.lines() {
+        if line.trim_start().starts_with("```") {
+            in_fence = !in_fence;
+            continue;
+        }
+        if in_fence || line.starts_with("    ") || line.starts_with('\t') {
+            lines.push(Line::from(Span::styled(line.to_string(), code)));
+            continue;
+        }
+        let trimmed = line.trim_start();
+        let indent = &line[..line.len() - trimmed.len()];
+        let heading = trimmed.trim_start_matches('#');
+        if heading.len() < trimmed.len() && heading.starts_with(' ') {
+            let style = Style::default().add_modifier(Modifier::BOLD | Modifier::UNDERLINED);
+            lines.push(Line::from(Span::styled(heading.trim().to_string(), style)));
+            continue;
+        }
+        let mut spans = vec![Span::raw(indent.to_string())];
+        let rest = if let Some(item) = ["- ", "* ", "+ "]
+            .iter()
+            .find_map(|bullet| trimmed.strip_prefix(bullet))
+        {
+            spans.push(Span::raw("• "));
+            item
+        } else if let Some(quote) = trimmed.strip_prefix("> ") {
+            spans.push(Span::styled(
+                "│ ",
+                Style::default().add_modifier(Modifier::DIM),
+            ));
+            quote
+        } else {
+            trimmed
+        };
+        spans.extend(inline(rest));
+        lines.push(Line::from(spans));
+    }
+    Text::from(lines)
+}
+
+/// `**bold**`, `*italic*` / `_italic_` and `` `code` `` as styled spans.
+/// Markers without a closing partner are kept as text.
+fn inline(text: &str) -> Vec<Span<'static>> {
+    let mut spans = Vec::new();
+    let mut plain = String::new();
+    let mut rest = text;
+    while let Some(c) = rest.chars().next() {
+        let styled = match c {
+            '`' => closed(rest, "`")
+                .map(|(inner, after)| (inner, Style::default().fg(Color::Yellow), after)),
+            '*' | '_' if rest[1..].starts_with(c) => {
+                let marker = &rest[..2];
+                closed(rest, marker).map(|(inner, after)| {
+                    (inner, Style::default().add_modifier(Modifier::BOLD), after)
+                })
+            }
+            // `_` inside a word, as in snake_case, isn't emphasis
+            '*' | '_' if c == '*' || !plain.ends_with(char::is_alphanumeric) => {
+                closed(rest, &rest[..1]).map(|(inner, after)| {
+                    (
+                        inner,
+                        Style::default().add_modifier(Modifier::ITALIC),
+                        after,
+                    )
+                })
+            }
+            _ => None,
+        };
+        match styled {
+            Some((inner, style, after)) => {
+                if !plain.is_empty() {
+                    spans.push(Span::raw(std::mem::take(&mut plain)));
+                }
+                spans.push(Span::styled(inner.to_string(), style));
+                rest = after;
+            }
+            None => {
+                plain.push(c);
+                rest = &rest[c.len_utf8()..];
+            }
+        }
+    }
+    if !plain.is_empty() {
+        spans.push(Span::raw(plain));
+    }
+    spans
+}
+
+/// The non-empty text between a leading `marker` and its next occurrence, and
+/// what follows that.
+fn closed<'a>(text: &'a str, marker: &str) -> Option<(&'a str, &'a str)> {
+    let body = &text[marker.len()..];
+    let end = body.find(marker).filter(|&end| end > 0)?;
+    Some((&body[..end], &body[end + marker.len()..]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn contents(spans: &[Span]) -> Vec<String> {
+        spans.iter().map(|s| s.content.to_string()).collect()
+    }
+
+    #[test]
+    fn inline_markers_become_styles() {
+        let spans = inline("a **bold** and *soft* `code`");
+        assert_eq!(
+            contents(&spans),
+            ["a ", "bold", " and ", "soft", " ", "code"]
+        );
+        assert!(spans[1].style.add_modifier.contains(Modifier::BOLD));
+        assert!(spans[3].style.add_modifier.contains(Modifier::ITALIC));
+        assert_eq!(spans[5].style.fg, Some(Color::Yellow));
+    }
+
+    #[test]
+    fn unclosed_markers_and_snake_case_stay_text() {
+        assert_eq!(contents(&inline("2 * 3 = 6")), ["2 * 3 = 6"]);
+        assert_eq!(
+            contents(&inline("call snake_case_name")),
+            ["call snake_case_name"]
+        );
+    }
+
+    #[test]
+    fn list_items_get_bullets() {
+        let text = render("- one\n  * two");
+        assert_eq!(text.lines[0].to_string(), "• one");
+        assert_eq!(text.lines[1].to_string(), "  • two");
+    }
+}
@@ -0,0 +1,827 @@
+use regex::Regex;
+use std::sync::LazyLock;
+
+use crate::config::{Config, ScopeFormat, TrailingNewline};
+
+/// The parts of a conventional commit message.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ParsedMessage {
+    pub commit_type: String,
+    /// Empty when the header has no scope.
+    pub scope: String,
+    pub breaking: bool,
+    pub description: String,
+    pub body: String,
+    pub footer: String,
+}
+
+/// The fields [`build_message`] makes a message from, as the form holds them
+/// once normalized.
+pub type MessageFields = ParsedMessage;
+
+/// How [`build_message`] lays out a message: the formatting half of
+/// [`Config`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FormatOptions<'a> {
+    /// Only the description and body, without type or scope.
+    pub freeform: bool,
+    pub scope_format: ScopeFormat,
+    /// Put between header, body and footer.
+    pub section_separator: &'a str,
+    /// Used for the header instead of `type(scope): description`; see
+    /// [`expand_header`].
+    pub header_template: Option<&'a str>,
+    /// The whole-message template; see [`fill_commit_template`].
+    pub commit_template: Option<&'a str>,
+    /// For the `{emoji}` placeholder of either template.
+    pub emoji: &'a str,
+}
+
+impl Default for FormatOptions<'_> {
+    fn default() -> Self {
+        FormatOptions {
+            freeform: false,
+            scope_format: ScopeFormat::default(),
+            section_separator: "\n\n",
+            header_template: None,
+            commit_template: None,
+            emoji: "",
+        }
+    }
+}
+
+impl<'a> FormatOptions<'a> {
+    /// The options `config` sets, with `emoji` for the type being written.
+    pub fn from_config(config: &'a Config, emoji: &'a str) -> FormatOptions<'a> {
+        FormatOptions {
+            freeform: config.freeform,
+            scope_format: config.scope_format,
+            section_separator: &config.section_separator,
+            header_template: config.header_template.as_deref(),
+            commit_template: config.commit_template_text.as_deref(),
+            emoji,
+        }
+    }
+}
+
+/// The commit message for `fields`, laid out as `opts` asks. Fields are used
+/// as given: trimming, wrapping and the like are up to the caller.
+pub fn build_message(fields: &MessageFields, opts: &FormatOptions) -> String {
+    let separator = opts.section_separator;
+    let message = if opts.freeform {
+        format_freeform_message(fields, separator)
+    } else {
+        match opts.header_template {
+            Some(template) => format_templated_message(template, opts.emoji, fields, separator),
+            None => format_message(fields, opts.scope_format, separator),
+        }
+    };
+    match opts.commit_template {
+        Some(template) => {
+            let header = message.lines().next().unwrap_or_default();
+            fill_commit_template(template, header, opts.emoji, fields)
+        }
+        None => message,
+    }
+}
+
+/// Parse `type(scope)!: description`, with the scope set off the way
+/// `scope_format` writes it. Returns `None` unless the line is a conventional
+/// header with a non-empty type and a `: ` separator.
+pub fn parse_header(line: &str, scope_format: ScopeFormat) -> Option<ParsedMessage> {
+    let (head, description) = line.split_once(':')?;
+    let description = description.strip_prefix(' ')?;
+    let (head, breaking) = match head.strip_suffix('!') {
+        Some(head) => (head, true),
+        None => (head, false),
+    };
+    let (open, close) = scope_format.delimiters();
+    let (commit_type, scope) = match head.split_once(open) {
+        Some((commit_type, rest)) => match close {
+            Some(close) => (commit_type, rest.strip_suffix(close)?),
+            None => (commit_type, rest),
+        },
+        None => (head, ""),
+    };
+    let valid_word = |s: &str| {
+        !s.chars()
+            .any(|c| c.is_whitespace() || "()[]!:".contains(c) || c == open)
+    };
+    if commit_type.is_empty() || !valid_word(commit_type) || !valid_word(scope) {
+        return None;
+    }
+    Some(ParsedMessage {
+        commit_type: commit_type.to_string(),
+        scope: scope.to_string(),
+        breaking,
+        description: description.to_string(),
+        ..ParsedMessage::default()
+    })
+}
+
+/// The part of a header being typed that `text` ends in, for completion.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum HeaderSegment<'a> {
+    /// The type so far: `fe` of `fe`.
+    Type(&'a str),
+    /// The scope so far: `pa` of `feat(pa`.
+    Scope(&'a str),
+}
+
+/// Which segment of `type(scope)!: description` the partial header `text`
+/// ends in, read the way `parse_header` splits a whole one. `None` past the
+/// scope, where there is nothing to complete.
+pub fn header_segment(text: &str) -> Option<HeaderSegment<'_>> {
+    let word = |s: &str| !s.chars().any(|c| c.is_whitespace() || "()!:".contains(c));
+    if text.contains(':') {
+        return None;
+    }
+    match text.split_once('(') {
+        Some((commit_type, scope)) if word(commit_type) && word(scope) => {
+            Some(HeaderSegment::Scope(scope))
+        }
+        Some(_) => None,
+        None if word(text) => Some(HeaderSegment::Type(text)),
+        None => None,
+    }
+}
+
+/// Subject prefixes of messages Git writes itself: merges, reverts, and the
+/// `fixup!`, `squash!` and `amend!` commits `git rebase --autosquash` folds in.
+const GIT_GENERATED: &[&str] = &[
+    "Merge branch ",
+    "Merge remote-tracking branch ",
+    "Merge tag ",
+    "Merge commit ",
+    "Merge pull request ",
+    "Revert \"",
+    "fixup! ",
+    "squash! ",
+    "amend! ",
+];
+
+/// Whether `subject` is one Git generated rather than one typed in, like
+/// `Merge branch 'main'` or `fixup! feat: add login`. commitlint ignores these
+/// by default, and so does `pre-form validate`.
+pub fn is_git_generated(subject: &str) -> bool {
+    GIT_GENERATED
+        .iter()
+        .any(|prefix| subject.starts_with(prefix))
+}
+
+/// Whether `line` is a git trailer such as `Refs: #12`, `Fixes #3` or
+/// `BREAKING CHANGE: ...`.
+pub fn is_trailer(line: &str) -> bool {
+    if line.starts_with("BREAKING CHANGE: ") || line.starts_with("BREAKING-CHANGE: ") {
+        return true;
+    }
+    let token_end = line.find([':', ' ']).unwrap_or(0);
+    let token = &line[..token_end];
+    let rest = &line[token_end..];
+    !token.is_empty()
+        && token.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+        && (rest.starts_with(": ") || rest.starts_with(" #"))
+}
+
+/// Parse a full message: header, then an optional body, then an optional
+/// footer (the last paragraph, if every line of it is a trailer).
+pub fn parse_commit_message(raw: &str, scope_format: ScopeFormat) -> Option<ParsedMessage> {
+    let (header, rest) = raw.split_once('\n').unwrap_or((raw, ""));
+    let mut parsed = parse_header(header, scope_format)?;
+    let rest = rest.trim_start_matches('\n').trim_end_matches('\n');
+    if rest.is_empty() {
+        return Some(parsed);
+    }
+    let (body, footer) = match rest.rsplit_once("\n\n") {
+        Some((body, last)) if last.lines().all(is_trailer) => (body, last),
+        None if rest.lines().all(is_trailer) => ("", rest),
+        _ => (rest, ""),
+    };
+    parsed.body = body.to_string();
+    parsed.footer = footer.to_string();
+    Some(parsed)
+}
+
+/// The message for `parts`: the header, then the body and footer as given,
+/// each after `separator` (normally one blank line) when present. Whitespace is
+/// kept byte for byte.
+pub fn format_message(parts: &ParsedMessage, scope_format: ScopeFormat, separator: &str) -> String {
+    let bang = if parts.breaking { "!" } else { "" };
+    let header = format!(
+        "{}{}{}: {}",
+        parts.commit_type,
+        scope_format.wrap(&parts.scope),
+        bang,
+        parts.description
+    );
+    join_sections(header, parts, separator)
+}
+
+/// Like [`format_message`], with the header made from `template` instead of
+/// `type(scope): description`. See [`expand_header`].
+pub fn format_templated_message(
+    template: &str,
+    emoji: &str,
+    parts: &ParsedMessage,
+    separator: &str,
+) -> String {
+    join_sections(expand_header(template, emoji, parts), parts, separator)
+}
+
+/// `{scope}` in a header template, with the punctuation around it.
+static SCOPE_PLACEHOLDER: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"[^\w\s{}]*\{scope\}[^\w\s{}]*\s?").expect("valid scope regex"));
+
+/// Fill `{type}`, `{emoji}`, `{scope}`, `{breaking}` (`!` or nothing) and
+/// `{description}` in a header template. Without a scope, `{scope}` goes
+/// together with the punctuation around it, so `{type}({scope}): ` becomes
+/// `feat: ` and `{emoji} {scope}: ` becomes `✨ `.
+pub fn expand_header(template: &str, emoji: &str, parts: &ParsedMessage) -> String {
+    let header = if parts.scope.is_empty() {
+        SCOPE_PLACEHOLDER.replace_all(template, "").into_owned()
+    } else {
+        template.replace("{scope}", &parts.scope)
+    };
+    header
+        .replace("{type}", &parts.commit_type)
+        .replace("{emoji}", emoji)
+        .replace("{breaking}", if parts.breaking { "!" } else { "" })
+        .replace("{description}", &parts.description)
+        .trim()
+        .to_string()
+}
+
+/// `{{`, `}}` and `{name}` in a `commit_template`.
+static COMMIT_TEMPLATE_PLACEHOLDER: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"\{\{|\}\}|\{([a-z]+)\}").expect("valid placeholder regex"));
+
+/// Fill a whole-message template: `{header}` (the header as it would be
+/// written otherwise), `{type}`, `{emoji}`, `{scope}`, `{breaking}`,
+/// `{description}`, `{body}` and `{footer}`. `{{` and `}}` stand for literal
+/// braces; unknown placeholders are kept as written. A line of nothing but
+/// placeholders that all came out empty is left out, together with a blank
+/// line it would leave doubled.
+pub fn fill_commit_template(
+    template: &str,
+    header: &str,
+    emoji: &str,
+    parts: &ParsedMessage,
+) -> String {
+    let value = |name: &str| match name {
+        "header" => Some(header),
+        "type" => Some(parts.commit_type.as_str()),
+        "emoji" => Some(emoji),
+        "scope" => Some(parts.scope.as_str()),
+        "breaking" => Some(if parts.breaking { "!" } else { "" }),
+        "description" => Some(parts.description.as_str()),
+        "body" => Some(parts.body.as_str()),
+        "footer" => Some(parts.footer.as_str()),
+        _ => None,
+    };
+    let mut lines: Vec<String> = Vec::new();
+    let mut dropped = false;
+    for line in template.lines() {
+        let mut known = false;
+        let filled = COMMIT_TEMPLATE_PLACEHOLDER.replace_all(line, |caps: &regex::Captures| {
+            match caps.get(1) {
+                None => caps[0][..1].to_string(),
+                Some(name) => match value(name.as_str()) {
+                    Some(value) => {
+                        known = true;
+                        value.to_string()
+                    }
+                    None => caps[0].to_string(),
+                },
+            }
+        });
+        if known && filled.trim().is_empty() {
+            dropped = true;
+            continue;
+        }
+        let blank = filled.trim().is_empty();
+        if blank && dropped && lines.last().is_some_and(|l| l.trim().is_empty()) {
+            continue;
+        }
+        dropped &= blank;
+        lines.push(filled.into_owned());
+    }
+    lines.join("\n").trim_end().to_string()
+}
+
+/// `message` ending the way `policy` asks, whatever newlines it ended with.
+pub fn with_trailing_newline(message: &str, policy: TrailingNewline) -> String {
+    let message = message.trim_end_matches('\n');
+    match policy {
+        TrailingNewline::Single => format!("{message}\n"),
+        TrailingNewline::None => message.to_string(),
+    }
+}
+
+/// The scope as written: `scope`, or `placeholder` when there is none.
+pub fn scope_or_placeholder(scope: String, placeholder: Option<&str>) -> String {
+    match placeholder {
+        Some(placeholder) if scope.is_empty() => placeholder.to_string(),
+        _ => scope,
+    }
+}
+
+/// A freeform message: the description as the subject line, then the body and
+/// footer.
+pub fn format_freeform_message(parts: &ParsedMessage, separator: &str) -> String {
+    join_sections(parts.description.clone(), parts, separator)
+}
+
+/// `header`, then the body and footer of `parts`, each after `separator`.
+fn join_sections(header: String, parts: &ParsedMessage, separator: &str) -> String {
+    let mut msg = header;
+    if !parts.body.is_empty() {
+        msg.push_str(separator);
+        msg.push_str(&parts.body);
+    }
+    if !parts.footer.is_empty() {
+        msg.push_str(separator);
+        msg.push_str(&parts.footer);
+    }
+    msg
+}
+
+/// Which part of `expected` comes back different when `message` is parsed, if
+/// any. Surrounding whitespace in the body and footer is not significant.
+pub fn round_trip_difference(
+    expected: &ParsedMessage,
+    message: &str,
+    scope_format: ScopeFormat,
+) -> Option<&'static str> {
+    let Some(parsed) = parse_commit_message(message, scope_format) else {
+        return Some("the header");
+    };
+    if parsed.commit_type != expected.commit_type
+        || parsed.scope != expected.scope
+        || parsed.breaking != expected.breaking
+    {
+        Some("the header")
+    } else if parsed.description != expected.description {
+        Some("the description")
+    } else if parsed.body.trim() != expected.body.trim() {
+        Some("the body")
+    } else if parsed.footer.trim() != expected.footer.trim() {
+        Some("the footer")
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn template_parts() -> ParsedMessage {
+        ParsedMessage {
+            commit_type: "feat".into(),
+            scope: "api".into(),
+            description: "add login".into(),
+            body: "Sessions last a day.".into(),
+            ..ParsedMessage::default()
+        }
+    }
+
+    #[test]
+    fn commit_template_fills_every_field() {
+        let template = "{header}\n\n## Summary\n{type} in {scope}: {description}\n\n## Details\n{body}\n\n{footer}";
+        let mut parts = template_parts();
+        parts.footer = "Refs: #12".into();
+        assert_eq!(
+            fill_commit_template(template, "feat(api): add login", "", &parts),
+            concat!(
+                "feat(api): add login\n\n",
+                "## Summary\nfeat in api: add login\n\n",
+                "## Details\nSessions last a day.\n\n",
+                "Refs: #12"
+            )
+        );
+    }
+
+    #[test]
+    fn empty_placeholder_lines_leave_no_gap() {
+        let template = "{header}\n\n{body}\n\n{footer}\n\nReviewed: yes";
+        let mut parts = template_parts();
+        parts.body.clear();
+        assert_eq!(
+            fill_commit_template(template, "feat(api): add login", "", &parts),
+            "feat(api): add login\n\nReviewed: yes"
+        );
+    }
+
+    #[test]
+    fn braces_escape_and_unknown_placeholders_stay() {
+        assert_eq!(
+            fill_commit_template(
+                "{{type}} is {type}, {ticket} stays",
+                "",
+                "",
+                &template_parts()
+            ),
+            "{type} is feat, {ticket} stays"
+        );
+    }
+
+    #[test]
+    fn headers_parse_in_their_scope_format() {
+        let parse = |line, format| {
+            let parsed = parse_header(line, format)?;
+            Some((parsed.commit_type, parsed.scope, parsed.breaking))
+        };
+        let expected = Some(("feat".to_string(), "api".to_string(), true));
+        assert_eq!(parse("feat(api)!: add", ScopeFormat::Parens), expected);
+        assert_eq!(parse("feat[api]!: add", ScopeFormat::Brackets), expected);
+        assert_eq!(parse("feat/api!: add", ScopeFormat::Slash), expected);
+        // another format's scope isn't taken for part of the type
+        assert_eq!(parse("feat(api): add", ScopeFormat::Brackets), None);
+        assert_eq!(parse("feat[api]: add", ScopeFormat::Parens), None);
+        assert_eq!(parse("feat[api: add", ScopeFormat::Brackets), None);
+    }
+
+    #[test]
+    fn git_generated_subjects_are_recognized() {
+        for subject in [
+            "Merge branch 'main' into feat/login",
+            "Merge remote-tracking branch 'origin/main'",
+            "Merge tag 'v1.2.0'",
+            "Merge commit 'a1b2c3d'",
+            "Merge pull request #12 from ann/login",
+            "Revert \"feat: add login\"",
+            "fixup! feat: add login",
+            "squash! feat: add login",
+            "amend! feat: add login",
+        ] {
+            assert!(is_git_generated(subject), "{subject}");
+        }
+        for subject in [
+            "feat: merge branches",
+            "Merged it",
+            "Reverting x",
+            "fixup: typo",
+        ] {
+            assert!(!is_git_generated(subject), "{subject}");
+        }
+    }
+
+    #[test]
+    fn header_segment_follows_the_cursor() {
+        assert_eq!(header_segment(""), Some(HeaderSegment::Type("")));
+        assert_eq!(header_segment("fe"), Some(HeaderSegment::Type("fe")));
+        assert_eq!(header_segment("feat("), Some(HeaderSegment::Scope("")));
+        assert_eq!(header_segment("feat(pa"), Some(HeaderSegment::Scope("pa")));
+        assert_eq!(header_segment("feat(parser)"), None);
+        assert_eq!(header_segment("feat!"), None);
+        assert_eq!(header_segment("feat: add"), None);
+    }
+
+    fn round_trip(message: &str) -> String {
+        format_message(
+            &parse_commit_message(message, ScopeFormat::Parens).unwrap(),
+            ScopeFormat::Parens,
+            "\n\n",
+        )
+    }
+
+    #[test]
+    fn indented_code_block_in_body_survives_round_trip() {
+        let message = concat!(
+            "feat(parser): accept nested blocks\n",
+            "\n",
+            "Example:\n",
+            "\n",
+            "    fn main() {\n",
+            "        run();\n",
+            "    }\n",
+            "\n",
+            "Refs: #12",
+        );
+        assert_eq!(round_trip(message), message);
+    }
+
+    #[test]
+    fn body_starting_with_code_keeps_its_indentation() {
+        let message = "fix: quote paths\n\n    git add -- \"$file\"\n\tls -la";
+        let parsed = parse_commit_message(message, ScopeFormat::Parens).unwrap();
+        assert_eq!(parsed.body, "    git add -- \"$file\"\n\tls -la");
+        assert_eq!(round_trip(message), message);
+    }
+
+    #[test]
+    fn sections_are_joined_with_the_separator() {
+        let parts = ParsedMessage {
+            commit_type: "feat".into(),
+            description: "add parser".into(),
+            body: "Details.".into(),
+            footer: "Refs: #1".into(),
+            ..ParsedMessage::default()
+        };
+        assert_eq!(
+            format_message(&parts, ScopeFormat::Parens, "\n\n\n"),
+            "feat: add parser\n\n\nDetails.\n\n\nRefs: #1"
+        );
+        let header_only = ParsedMessage {
+            body: String::new(),
+            footer: String::new(),
+            ..parts
+        };
+        assert_eq!(
+            format_message(&header_only, ScopeFormat::Parens, "\n\n\n"),
+            "feat: add parser"
+        );
+    }
+
+    #[test]
+    fn empty_scope_is_left_out_without_a_placeholder() {
+        let parts = ParsedMessage {
+            commit_type: "feat".into(),
+            scope: scope_or_placeholder(String::new(), None),
+            description: "add parser".into(),
+            ..ParsedMessage::default()
+        };
+        assert_eq!(
+            format_message(&parts, ScopeFormat::Parens, "\n\n"),
+            "feat: add parser"
+        );
+    }
+
+    #[test]
+    fn empty_scope_takes_the_placeholder() {
+        let parts = ParsedMessage {
+            commit_type: "feat".into(),
+            scope: scope_or_placeholder(String::new(), Some("*")),
+            description: "add parser".into(),
+            ..ParsedMessage::default()
+        };
+        assert_eq!(
+            format_message(&parts, ScopeFormat::Parens, "\n\n"),
+            "feat(*): add parser"
+        );
+        assert_eq!(scope_or_placeholder("api".into(), Some("*")), "api");
+    }
+
+    #[test]
+    fn freeform_message_is_subject_and_body() {
+        let parts = ParsedMessage {
+            commit_type: "feat".into(),
+            scope: "api".into(),
+            breaking: true,
+            description: "Fix the login redirect".into(),
+            body: "It looped on expired sessions.".into(),
+            ..ParsedMessage::default()
+        };
+        assert_eq!(
+            format_freeform_message(&parts, "\n\n"),
+            "Fix the login redirect\n\nIt looped on expired sessions."
+        );
+    }
+
+    #[test]
+    fn trailing_newline_policy() {
+        for message in ["feat: x", "feat: x\n", "feat: x\n\n\n"] {
+            assert_eq!(
+                with_trailing_newline(message, TrailingNewline::Single),
+                "feat: x\n"
+            );
+            assert_eq!(
+                with_trailing_newline(message, TrailingNewline::None),
+                "feat: x"
+            );
+        }
+        // trailing spaces on the last line aren't newlines
+        assert_eq!(
+            with_trailing_newline("feat: x\n\nbody  ", TrailingNewline::Single),
+            "feat: x\n\nbody  \n"
+        );
+    }
+
+    #[test]
+    fn scope_format_presets() {
+        let mut parts = ParsedMessage {
+            commit_type: "feat".into(),
+            description: "add login".into(),
+            ..ParsedMessage::default()
+        };
+        for format in [
+            ScopeFormat::Parens,
+            ScopeFormat::Brackets,
+            ScopeFormat::Slash,
+        ] {
+            assert_eq!(format_message(&parts, format, "\n\n"), "feat: add login");
+        }
+        parts.scope = "auth".into();
+        parts.breaking = true;
+        assert_eq!(
+            format_message(&parts, ScopeFormat::Parens, "\n\n"),
+            "feat(auth)!: add login"
+        );
+        assert_eq!(
+            format_message(&parts, ScopeFormat::Brackets, "\n\n"),
+            "feat[auth]!: add login"
+        );
+        assert_eq!(
+            format_message(&parts, ScopeFormat::Slash, "\n\n"),
+            "feat/auth!: add login"
+        );
+    }
+
+    #[test]
+    fn header_template_drops_missing_scope_with_its_punctuation() {
+        let mut parts = ParsedMessage {
+            commit_type: "feat".into(),
+            description: "add login".into(),
+            ..ParsedMessage::default()
+        };
+        assert_eq!(
+            expand_header("{emoji} {description}", "✨", &parts),
+            "✨ add login"
+        );
+        assert_eq!(
+            expand_header("{emoji} {scope}: {description}", "✨", &parts),
+            "✨ add login"
+        );
+        assert_eq!(
+            expand_header("{type}({scope}){breaking}: {description}", "", &parts),
+            "feat: add login"
+        );
+        parts.scope = "auth".into();
+        parts.breaking = true;
+        assert_eq!(
+            expand_header("{emoji} {scope}: {description}", "✨", &parts),
+            "✨ auth: add login"
+        );
+        assert_eq!(
+            expand_header("{type}({scope}){breaking}: {description}", "", &parts),
+            "feat(auth)!: add login"
+        );
+    }
+
+    fn fields() -> MessageFields {
+        MessageFields {
+            commit_type: "feat".into(),
+            scope: "auth".into(),
+            breaking: true,
+            description: "add login".into(),
+            body: "Sessions last a day.".into(),
+            footer: "Refs: #12".into(),
+        }
+    }
+
+    #[test]
+    fn build_message_defaults_to_a_conventional_commit() {
+        assert_eq!(
+            build_message(&fields(), &FormatOptions::default()),
+            "feat(auth)!: add login\n\nSessions last a day.\n\nRefs: #12"
+        );
+        let header_only = MessageFields {
+            scope: String::new(),
+            breaking: false,
+            body: String::new(),
+            footer: String::new(),
+            ..fields()
+        };
+        assert_eq!(
+            build_message(&header_only, &FormatOptions::default()),
+            "feat: add login"
+        );
+    }
+
+    #[test]
+    fn build_message_scope_format_and_separator_matrix() {
+        let cases = [
+            (ScopeFormat::Parens, "feat(auth)!: add login"),
+            (ScopeFormat::Brackets, "feat[auth]!: add login"),
+            (ScopeFormat::Slash, "feat/auth!: add login"),
+        ];
+        for (scope_format, header) in cases {
+            for separator in ["\n\n", "\n\n\n", "\n"] {
+                let opts = FormatOptions {
+                    scope_format,
+                    section_separator: separator,
+                    ..FormatOptions::default()
+                };
+                assert_eq!(
+                    build_message(&fields(), &opts),
+                    format!("{header}{separator}Sessions last a day.{separator}Refs: #12"),
+                    "{scope_format:?} with {separator:?}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn build_message_skips_missing_sections() {
+        let opts = FormatOptions::default();
+        let no_body = MessageFields {
+            body: String::new(),
+            ..fields()
+        };
+        assert_eq!(
+            build_message(&no_body, &opts),
+            "feat(auth)!: add login\n\nRefs: #12"
+        );
+        let no_footer = MessageFields {
+            footer: String::new(),
+            ..fields()
+        };
+        assert_eq!(
+            build_message(&no_footer, &opts),
+            "feat(auth)!: add login\n\nSessions last a day."
+        );
+    }
+
+    #[test]
+    fn build_message_header_template_ignores_scope_format() {
+        for scope_format in [ScopeFormat::Parens, ScopeFormat::Slash] {
+            let opts = FormatOptions {
+                scope_format,
+                header_template: Some("{emoji} {scope}: {description}"),
+                emoji: "✨",
+                ..FormatOptions::default()
+            };
+            assert_eq!(
+                build_message(&fields(), &opts),
+                "✨ auth: add login\n\nSessions last a day.\n\nRefs: #12"
+            );
+        }
+    }
+
+    #[test]
+    fn build_message_freeform_wins_over_header_template() {
+        let opts = FormatOptions {
+            freeform: true,
+            header_template: Some("{type}: {description}"),
+            section_separator: "\n\n\n",
+            ..FormatOptions::default()
+        };
+        assert_eq!(
+            build_message(&fields(), &opts),
+            "add login\n\n\nSessions last a day.\n\n\nRefs: #12"
+        );
+    }
+
+    #[test]
+    fn build_message_commit_template_gets_the_formatted_header() {
+        let template = "{header}\n\n## Details\n{body}\n\n{footer}";
+        let mut opts = FormatOptions {
+            scope_format: ScopeFormat::Brackets,
+            commit_template: Some(template),
+            ..FormatOptions::default()
+        };
+        assert_eq!(
+            build_message(&fields(), &opts),
+            "feat[auth]!: add login\n\n## Details\nSessions last a day.\n\nRefs: #12"
+        );
+        opts.header_template = Some("{emoji} {description}");
+        opts.emoji = "✨";
+        assert_eq!(
+            build_message(&fields(), &opts),
+            "✨ add login\n\n## Details\nSessions last a day.\n\nRefs: #12"
+        );
+        // the separator only joins sections the template doesn't lay out
+        opts.section_separator = "\n\n\n";
+        let no_body = MessageFields {
+            body: String::new(),
+            ..fields()
+        };
+        assert_eq!(
+            build_message(&no_body, &opts),
+            "✨ add login\n\n## Details\n\nRefs: #12"
+        );
+    }
+
+    #[test]
+    fn options_follow_the_config() {
+        let config = Config {
+            freeform: true,
+            scope_format: ScopeFormat::Slash,
+            section_separator: "\n\n\n".into(),
+            header_template: Some("{emoji} {description}".into()),
+            commit_template_text: Some("{header}".into()),
+            ..Config::default()
+        };
+        assert_eq!(
+            FormatOptions::from_config(&config, "🐛"),
+            FormatOptions {
+                freeform: true,
+                scope_format: ScopeFormat::Slash,
+                section_separator: "\n\n\n",
+                header_template: Some("{emoji} {description}"),
+                commit_template: Some("{header}"),
+                emoji: "🐛",
+            }
+        );
+        assert_eq!(
+            FormatOptions::from_config(&Config::default(), ""),
+            FormatOptions::default()
+        );
+    }
+
+    #[test]
+    fn trailing_spaces_inside_body_are_kept() {
+        let message = "docs: hard breaks\n\nfirst line  \nsecond line\n\nthird";
+        assert_eq!(round_trip(message), message);
+    }
+}
@@ -0,0 +1,167 @@
+use anyhow::{Context, Result};
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, Write};
+
+use crate::config::Severity;
+use crate::validate::validate;
+use crate::{App, Focus, Outcome, TextInput};
+
+/// Fill the form by plain questions on stderr, for when the TUI can't take
+/// over the terminal. Answers come from the controlling terminal if there is
+/// one (Git hooks get no stdin), else from stdin. Running out of input aborts.
+pub fn run(app: &mut App) -> Result<Outcome> {
+    let mut input: Box<dyn BufRead> = match File::open("/dev/tty") {
+        Ok(tty) => Box::new(BufReader::new(tty)),
+        Err(_) => Box::new(io::stdin().lock()),
+    };
+    if !ask_all(app, &mut input)? {
+        return Ok(Outcome::Aborted);
+    }
+    let issues = validate(app);
+    for issue in &issues {
+        let level = match issue.severity {
+            Severity::Warn => "warning: ",
+            _ => "",
+        };
+        eprintln!("pre-form: {level}{}", issue.message);
+    }
+    Ok(if issues.iter().any(|i| i.severity == Severity::Error) {
+        Outcome::Invalid
+    } else {
+        Outcome::Written
+    })
+}
+
+/// `false` when input ran out before the form was complete.
+fn ask_all(app: &mut App, input: &mut dyn BufRead) -> Result<bool> {
+    if app.config.freeform {
+        return ask_freeform(app, input);
+    }
+    let names: Vec<&str> = app.types.iter().map(|t| t.name.as_str()).collect();
+    let question = format!(
+        "Type ({}) [{}]: ",
+        names.join(", "),
+        app.types[app.type_idx].name
+    );
+    loop {
+        let Some(answer) = ask(input, &question)? else {
+            return Ok(false);
+        };
+        if answer.is_empty() {
+            break;
+        }
+        match app.types.iter().position(|t| t.name == answer) {
+            Some(idx) => {
+                app.select_type(idx);
+                break;
+            }
+            None => eprintln!("unknown type `{answer}`"),
+        }
+    }
+
+    let required = app.types[app.type_idx].require_scope;
+    let Some(scope) = ask(
+        input,
+        if required {
+            "Scope (required): "
+        } else {
+            "Scope (optional): "
+        },
+    )?
+    else {
+        return Ok(false);
+    };
+    app.scope = TextInput::from(scope);
+
+    let Some(breaking) = ask(input, "Breaking change? [y/N]: ")? else {
+        return Ok(false);
+    };
+    app.breaking = breaking.eq_ignore_ascii_case("y");
+
+    let Some(description) = ask(input, "Description: ")? else {
+        return Ok(false);
+    };
+    app.description = TextInput::from(description);
+
+    if app.field_enabled(Focus::Ticket) {
+        let question = match app.ticket.value.as_str() {
+            "" => "Ticket (optional): ".to_string(),
+            current => format!("Ticket [{current}]: "),
+        };
+        let Some(ticket) = ask(input, &question)? else {
+            return Ok(false);
+        };
+        if !ticket.is_empty() {
+            app.ticket = TextInput::from(ticket);
+        }
+    }
+
+    if app.field_enabled(Focus::Body) {
+        let Some(body) = ask_lines(input, "Body (end with an empty line):")? else {
+            return Ok(false);
+        };
+        if !body.is_empty() {
+            app.body = TextInput::from(body);
+        }
+    }
+    if app.field_enabled(Focus::Footer) {
+        let Some(footer) = ask_lines(input, "Footer (end with an empty line):")? else {
+            return Ok(false);
+        };
+        if !footer.is_empty() {
+            app.set_footer(&footer);
+        }
+    }
+    Ok(true)
+}
+
+/// The subject and body of a freeform message.
+fn ask_freeform(app: &mut App, input: &mut dyn BufRead) -> Result<bool> {
+    let Some(subject) = ask(input, "Subject: ")? else {
+        return Ok(false);
+    };
+    app.description = TextInput::from(subject);
+    let Some(body) = ask_lines(input, "Body (end with an empty line):")? else {
+        return Ok(false);
+    };
+    if !body.is_empty() {
+        app.body = TextInput::from(body);
+    }
+    Ok(true)
+}
+
+/// One trimmed answer, or `None` at end of input.
+fn ask(input: &mut dyn BufRead, question: &str) -> Result<Option<String>> {
+    eprint!("{question}");
+    io::stderr().flush().context("failed to write prompt")?;
+    let mut line = String::new();
+    if input
+        .read_line(&mut line)
+        .context("failed to read answer")?
+        == 0
+    {
+        return Ok(None);
+    }
+    Ok(Some(line.trim().to_string()))
+}
+
+/// Lines up to the first empty one, or `None` at end of input.
+fn ask_lines(input: &mut dyn BufRead, question: &str) -> Result<Option<String>> {
+    eprintln!("{question}");
+    let mut lines = Vec::new();
+    loop {
+        let mut line = String::new();
+        if input
+            .read_line(&mut line)
+            .context("failed to read answer")?
+            == 0
+        {
+            return Ok(None);
+        }
+        let line = line.trim_end_matches(['\r', '\n']);
+        if line.is_empty() {
+            return Ok(Some(lines.join("\n")));
+        }
+        lines.push(line.to_string());
+    }
+}
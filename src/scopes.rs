@@ -0,0 +1,409 @@
+use std::collections::HashMap;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use crate::config::ScopeCase;
+use crate::{PreformError, preform_dir};
+
+/// A known scope from `scopes.txt`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Scope {
+    pub name: String,
+    pub description: Option<String>,
+    /// How many written commits used this scope (from `scope-usage.txt`).
+    pub uses: u32,
+    /// Suggested because staged files belong to a package of this name.
+    pub detected: bool,
+}
+
+pub fn scopes_file() -> PathBuf {
+    preform_dir().join("scopes.txt")
+}
+
+/// Sidecar of `name<TAB>count` lines, kept apart so `scopes.txt` stays hand-editable.
+pub fn usage_file() -> PathBuf {
+    preform_dir().join("scope-usage.txt")
+}
+
+/// Parse one `scopes.txt` line: either `name` or `name<TAB>description`.
+/// Blank lines and `#` comments yield `None`.
+pub fn parse_scope_line(line: &str) -> Option<Scope> {
+    let line = line.trim_end();
+    if line.trim_start().starts_with('#') {
+        return None;
+    }
+    let (name, description) = match line.split_once('\t') {
+        Some((name, description)) => (name, Some(description.trim())),
+        None => (line, None),
+    };
+    let name = name.trim();
+    if name.is_empty() {
+        return None;
+    }
+    Some(Scope {
+        name: name.to_string(),
+        description: description.filter(|d| !d.is_empty()).map(str::to_string),
+        uses: 0,
+        detected: false,
+    })
+}
+
+/// Parse a whole file, merging repeated names (the first description wins).
+pub fn parse_scopes(raw: &str) -> Vec<Scope> {
+    let mut scopes: Vec<Scope> = Vec::new();
+    for scope in raw.lines().filter_map(parse_scope_line) {
+        match scopes.iter_mut().find(|s| s.name == scope.name) {
+            Some(known) => {
+                if known.description.is_none() {
+                    known.description = scope.description;
+                }
+            }
+            None => scopes.push(scope),
+        }
+    }
+    scopes
+}
+
+/// `name<TAB>count` lines; malformed ones are skipped and repeated names
+/// added up.
+fn parse_usage(raw: &str) -> Vec<(String, u32)> {
+    let mut usage: Vec<(String, u32)> = Vec::new();
+    for line in raw.lines() {
+        let Some((name, count)) = line.split_once('\t') else {
+            continue;
+        };
+        let Ok(count) = count.trim().parse::<u32>() else {
+            continue;
+        };
+        match usage.iter_mut().find(|(known, _)| known == name) {
+            Some((_, total)) => *total = total.saturating_add(count),
+            None => usage.push((name.to_string(), count)),
+        }
+    }
+    usage
+}
+
+/// Known scopes, most used first; ties keep their `scopes.txt` order.
+pub fn load_scopes() -> Vec<Scope> {
+    let mut scopes = fs::read_to_string(scopes_file())
+        .map(|raw| parse_scopes(&raw))
+        .unwrap_or_default();
+    let usage = parse_usage(&fs::read_to_string(usage_file()).unwrap_or_default());
+    for scope in &mut scopes {
+        if let Some((_, count)) = usage.iter().find(|(name, _)| *name == scope.name) {
+            scope.uses = *count;
+        }
+    }
+    scopes.sort_by_key(|s| std::cmp::Reverse(s.uses));
+    scopes
+}
+
+/// Count one more commit using `name`.
+pub fn record_scope_use(name: &str) -> Result<(), PreformError> {
+    let mut usage = parse_usage(&fs::read_to_string(usage_file()).unwrap_or_default());
+    match usage.iter_mut().find(|(known, _)| known == name) {
+        Some((_, count)) => *count += 1,
+        None => usage.push((name.to_string(), 1)),
+    }
+    let raw: String = usage
+        .iter()
+        .map(|(name, count)| format!("{name}\t{count}\n"))
+        .collect();
+    fs::create_dir_all(preform_dir()).map_err(PreformError::io(preform_dir()))?;
+    fs::write(usage_file(), raw).map_err(PreformError::io(usage_file()))
+}
+
+/// Append a scope; the description column is only written when there is one.
+pub fn persist_new_scope(scope: &mut Scope, case: Option<ScopeCase>) -> Result<(), PreformError> {
+    if let Some(case) = case {
+        scope.name = normalize_scope(&scope.name, case);
+    }
+    fs::create_dir_all(preform_dir()).map_err(PreformError::io(preform_dir()))?;
+    let mut f = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(scopes_file())
+        .map_err(PreformError::io(scopes_file()))?;
+    match &scope.description {
+        Some(description) => writeln!(f, "{}\t{}", scope.name, description),
+        None => writeln!(f, "{}", scope.name),
+    }
+    .map_err(PreformError::io(scopes_file()))
+}
+
+/// Drop every `scopes.txt` line naming exactly `name`, keeping the rest of the
+/// file as written. Returns whether anything was removed.
+pub fn remove_scope(name: &str) -> Result<bool, PreformError> {
+    let raw = match fs::read_to_string(scopes_file()) {
+        Ok(raw) => raw,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(false),
+        Err(source) => {
+            return Err(PreformError::Io {
+                path: scopes_file(),
+                source,
+            });
+        }
+    };
+    let Some(rewritten) = without_scope(&raw, name) else {
+        return Ok(false);
+    };
+    fs::write(scopes_file(), rewritten).map_err(PreformError::io(scopes_file()))?;
+    Ok(true)
+}
+
+/// `raw` without the lines naming exactly `name`; `None` when there are none.
+fn without_scope(raw: &str, name: &str) -> Option<String> {
+    let kept: Vec<&str> = raw
+        .lines()
+        .filter(|line| parse_scope_line(line).is_none_or(|scope| scope.name != name))
+        .collect();
+    if kept.len() == raw.lines().count() {
+        return None;
+    }
+    let mut rewritten = kept.join("\n");
+    if !rewritten.is_empty() {
+        rewritten.push('\n');
+    }
+    Some(rewritten)
+}
+
+/// `scope` spelled the `case` way. Kebab case breaks words at spaces,
+/// punctuation and lower-to-upper changes: `HTTPServer` becomes `http-server`.
+pub fn normalize_scope(scope: &str, case: ScopeCase) -> String {
+    let scope = scope.trim();
+    if case == ScopeCase::Lower {
+        return scope.to_lowercase();
+    }
+    let chars: Vec<char> = scope.chars().collect();
+    let mut words: Vec<String> = vec![String::new()];
+    for (i, &c) in chars.iter().enumerate() {
+        if !c.is_alphanumeric() {
+            words.push(String::new());
+            continue;
+        }
+        let prev = i.checked_sub(1).map(|p| chars[p]);
+        let next = chars.get(i + 1);
+        let boundary = c.is_uppercase()
+            && prev.is_some_and(|p| {
+                p.is_lowercase()
+                    || p.is_ascii_digit()
+                    || (p.is_uppercase() && next.is_some_and(|n| n.is_lowercase()))
+            });
+        if boundary {
+            words.push(String::new());
+        }
+        words
+            .last_mut()
+            .expect("starts with a word")
+            .extend(c.to_lowercase());
+    }
+    words.retain(|w| !w.is_empty());
+    words.join("-")
+}
+
+/// Name of the Cargo crate or npm package rooted at `dir`, if any.
+fn package_name(dir: &Path) -> Option<String> {
+    if let Ok(raw) = fs::read_to_string(dir.join("Cargo.toml"))
+        && let Ok(manifest) = raw.parse::<toml::Table>()
+        && let Some(name) = manifest
+            .get("package")
+            .and_then(|p| p.get("name"))
+            .and_then(|n| n.as_str())
+    {
+        return Some(name.to_string());
+    }
+    let raw = fs::read_to_string(dir.join("package.json")).ok()?;
+    let json: serde_json::Value = serde_json::from_str(&raw).ok()?;
+    let name = json.get("name")?.as_str()?;
+    // `@org/pkg` reads better as a scope without the org
+    Some(name.rsplit('/').next().unwrap_or(name).to_string())
+}
+
+/// Scope candidates for the staged `files`: the nearest enclosing crate or npm
+/// package of each file, ranked by how many files it contains. The package at
+/// the repository root is skipped since it covers everything.
+pub fn detect_scopes(files: &[String]) -> Vec<String> {
+    detect_scopes_in(Path::new(""), files)
+}
+
+/// [`detect_scopes`] for a repository at `root`.
+fn detect_scopes_in(root: &Path, files: &[String]) -> Vec<String> {
+    let mut by_dir: HashMap<PathBuf, Option<String>> = HashMap::new();
+    let mut counts: Vec<(String, usize)> = Vec::new();
+    for file in files {
+        let Some(parent) = Path::new(file).parent() else {
+            continue;
+        };
+        let found = parent
+            .ancestors()
+            .filter(|dir| !dir.as_os_str().is_empty())
+            .find_map(|dir| {
+                by_dir
+                    .entry(dir.to_path_buf())
+                    .or_insert_with(|| package_name(&root.join(dir)))
+                    .clone()
+            });
+        if let Some(name) = found {
+            match counts.iter_mut().find(|(known, _)| *known == name) {
+                Some((_, count)) => *count += 1,
+                None => counts.push((name, 1)),
+            }
+        }
+    }
+    counts.sort_by_key(|(_, count)| std::cmp::Reverse(*count));
+    counts.into_iter().map(|(name, _)| name).collect()
+}
+
+/// Put `detected` scopes first, in rank order, adding any that aren't known yet.
+pub fn merge_detected(scopes: &mut Vec<Scope>, detected: &[String]) {
+    for name in detected.iter().rev() {
+        let scope = match scopes.iter().position(|s| s.name == *name) {
+            Some(idx) => scopes.remove(idx),
+            None => Scope {
+                name: name.clone(),
+                description: None,
+                uses: 0,
+                detected: false,
+            },
+        };
+        scopes.insert(
+            0,
+            Scope {
+                detected: true,
+                ..scope
+            },
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scope(name: &str, description: Option<&str>) -> Scope {
+        Scope {
+            name: name.to_string(),
+            description: description.map(str::to_string),
+            uses: 0,
+            detected: false,
+        }
+    }
+
+    #[test]
+    fn scope_lines_with_and_without_descriptions() {
+        assert_eq!(
+            parse_scope_line("api\tREST endpoints"),
+            Some(scope("api", Some("REST endpoints")))
+        );
+        assert_eq!(parse_scope_line("  ui  "), Some(scope("ui", None)));
+        assert_eq!(parse_scope_line("cli\t  "), Some(scope("cli", None)));
+        assert_eq!(parse_scope_line(""), None);
+        assert_eq!(parse_scope_line("   "), None);
+        assert_eq!(parse_scope_line("\tno name"), None);
+        assert_eq!(parse_scope_line("# shared scopes"), None);
+    }
+
+    #[test]
+    fn scope_files_skip_comments_and_merge_repeats() {
+        let raw = "# scopes\napi\n\nui\tScreens\napi\tREST endpoints\nui\tignored\n";
+        assert_eq!(
+            parse_scopes(raw),
+            vec![
+                scope("api", Some("REST endpoints")),
+                scope("ui", Some("Screens")),
+            ]
+        );
+    }
+
+    #[test]
+    fn usage_counts_skip_malformed_lines_and_add_up_repeats() {
+        let raw = "api\t3\nui\tmany\nplain\n\ncli\t 2 \napi\t4\n";
+        assert_eq!(
+            parse_usage(raw),
+            vec![("api".to_string(), 7), ("cli".to_string(), 2)]
+        );
+        assert_eq!(parse_usage(""), Vec::new());
+    }
+
+    /// A scratch repository holding `files`, each with its contents.
+    fn repo(name: &str, files: &[(&str, &str)]) -> PathBuf {
+        let root = std::env::temp_dir().join(format!("pre-form-{name}-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        for (path, contents) in files {
+            let path = root.join(path);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, contents).unwrap();
+        }
+        root
+    }
+
+    fn strings(items: &[&str]) -> Vec<String> {
+        items.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn nearest_package_wins_and_the_root_one_is_skipped() {
+        let root = repo(
+            "detect",
+            &[
+                ("Cargo.toml", "[package]\nname = \"workspace\"\n"),
+                ("crates/parser/Cargo.toml", "[package]\nname = \"parser\"\n"),
+                ("web/package.json", r#"{"name": "@acme/web"}"#),
+            ],
+        );
+        let files = strings(&[
+            "web/src/app.ts",
+            "crates/parser/src/lib.rs",
+            "crates/parser/src/deep/nested/mod.rs",
+            "README.md",
+            "src/main.rs",
+        ]);
+        assert_eq!(detect_scopes_in(&root, &files), strings(&["parser", "web"]));
+        assert_eq!(
+            detect_scopes_in(&root, &strings(&["README.md", "src/main.rs"])),
+            Vec::<String>::new()
+        );
+        fs::remove_dir_all(root).unwrap();
+    }
+
+    #[test]
+    fn detected_scopes_come_first_even_once_removed() {
+        let raw = "api\tREST endpoints\nparser\tThe grammar\nui\n";
+        let rewritten = without_scope(raw, "parser").unwrap();
+        assert_eq!(rewritten, "api\tREST endpoints\nui\n");
+        assert_eq!(without_scope(&rewritten, "parser"), None);
+
+        let mut scopes = parse_scopes(&rewritten);
+        merge_detected(&mut scopes, &strings(&["parser", "ui"]));
+        let names: Vec<(&str, bool)> = scopes
+            .iter()
+            .map(|s| (s.name.as_str(), s.detected))
+            .collect();
+        assert_eq!(names, [("parser", true), ("ui", true), ("api", false)]);
+        // the removed description doesn't come back with it
+        assert_eq!(scopes[0].description, None);
+    }
+
+    #[test]
+    fn lower_case_only_lowercases() {
+        let lower = |s| normalize_scope(s, ScopeCase::Lower);
+        assert_eq!(lower("HTTPServer"), "httpserver");
+        assert_eq!(lower("api_client"), "api_client");
+        assert_eq!(lower("Foo Bar"), "foo bar");
+        assert_eq!(lower("  -Api-  "), "-api-");
+        assert_eq!(lower(""), "");
+    }
+
+    #[test]
+    fn kebab_case_breaks_words() {
+        let kebab = |s| normalize_scope(s, ScopeCase::Kebab);
+        assert_eq!(kebab("HTTPServer"), "http-server");
+        assert_eq!(kebab("api_client"), "api-client");
+        assert_eq!(kebab("Foo Bar"), "foo-bar");
+        assert_eq!(kebab("apiClient2Go"), "api-client2-go");
+        assert_eq!(kebab("--api__client--"), "api-client");
+        assert_eq!(kebab(" _ "), "");
+        assert_eq!(kebab(""), "");
+    }
+}
@@ -0,0 +1,238 @@
+use regex::Regex;
+use std::sync::LazyLock;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::git;
+
+/// Values for `{name}` placeholders in `footer_template`.
+#[derive(Debug, Clone, Default)]
+pub struct Variables {
+    /// `{branch}`: the checked-out branch.
+    pub branch: Option<String>,
+    /// `{ticket}`: a tracker key from the branch name, `ABC-123` or `#123`.
+    pub ticket: Option<String>,
+    /// `{date}`: today, as `YYYY-MM-DD` (UTC).
+    pub date: String,
+}
+
+impl Variables {
+    pub fn from_repo() -> Variables {
+        let branch = git::current_branch();
+        Variables {
+            ticket: branch.as_deref().and_then(ticket_from_branch),
+            branch,
+            date: today(),
+        }
+    }
+
+    fn get(&self, name: &str) -> Option<Option<&str>> {
+        match name {
+            "branch" => Some(self.branch.as_deref()),
+            "ticket" => Some(self.ticket.as_deref()),
+            "date" => Some(Some(&self.date)),
+            _ => None,
+        }
+    }
+}
+
+/// A tracker key such as `ABC-123`.
+static TICKET_KEY: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"[A-Z][A-Z0-9]+-\d+").expect("valid ticket regex"));
+
+/// An issue number: `#123`, or a number opening a path segment (`123-login`,
+/// `fix/123`). Digits inside a word, as in `oauth2`, don't count.
+static TICKET_NUMBER: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"#(\d+)|(?:^|/)(\d+)(?:[-_/]|$)").expect("valid number regex"));
+
+/// The first `ABC-123` key in `branch`, else its first issue number as `#123`.
+pub fn ticket_from_branch(branch: &str) -> Option<String> {
+    if let Some(m) = TICKET_KEY.find(branch) {
+        return Some(m.as_str().to_string());
+    }
+    let caps = TICKET_NUMBER.captures(branch)?;
+    let number = caps.get(1).or_else(|| caps.get(2))?;
+    Some(format!("#{}", number.as_str()))
+}
+
+/// A ticket `pattern` matches at the start of `description`, bare or in
+/// brackets and followed by `:` or `-`, split from the rest of the description:
+/// `JIRA-123: add login` gives `JIRA-123` and `add login`. `None` when there
+/// is no such ticket, or nothing would be left of the description.
+pub fn split_leading_ticket(description: &str, pattern: &Regex) -> Option<(String, String)> {
+    let text = description.trim_start();
+    let (inner, bracketed) = match text.strip_prefix('[') {
+        Some(inner) => (inner, true),
+        None => (text, false),
+    };
+    let ticket = pattern
+        .find(inner)
+        .filter(|m| m.start() == 0 && !m.is_empty())?;
+    let mut rest = &inner[ticket.end()..];
+    if bracketed {
+        rest = rest.strip_prefix(']')?;
+    } else if rest.starts_with(|c: char| !c.is_whitespace() && c != ':') {
+        // part of a longer word, like `ABC-1234x`
+        return None;
+    }
+    let rest = rest.trim_start();
+    let rest = rest.strip_prefix([':', '-']).unwrap_or(rest).trim_start();
+    if rest.is_empty() {
+        return None;
+    }
+    Some((ticket.as_str().to_string(), rest.to_string()))
+}
+
+/// A `{name}` placeholder in `footer_template`.
+static PLACEHOLDER: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"\{([a-z]+)\}").expect("valid placeholder regex"));
+
+/// Replace the known `{name}` placeholders in `template`. A line with a
+/// variable that has no value (no ticket in the branch, say) is left out;
+/// unknown placeholders are kept as written.
+pub fn expand(template: &str, vars: &Variables) -> String {
+    template
+        .lines()
+        .filter_map(|line| {
+            let mut missing = false;
+            let expanded =
+                PLACEHOLDER.replace_all(line, |caps: &regex::Captures| match vars.get(&caps[1]) {
+                    Some(Some(value)) => value.to_string(),
+                    Some(None) => {
+                        missing = true;
+                        String::new()
+                    }
+                    None => caps[0].to_string(),
+                });
+            (!missing).then(|| expanded.into_owned())
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Today's UTC date as `YYYY-MM-DD`.
+fn today() -> String {
+    let secs = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs());
+    let (year, month, day) = civil_date(secs as i64 / 86_400);
+    format!("{year:04}-{month:02}-{day:02}")
+}
+
+/// Year, month and day of `days` since 1970-01-01.
+pub(crate) fn civil_date(days: i64) -> (i64, i64, i64) {
+    // Howard Hinnant's algorithm
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn split(description: &str) -> Option<(String, String)> {
+        let pattern = Regex::new(r"[A-Z][A-Z0-9]+-\d+").unwrap();
+        split_leading_ticket(description, &pattern)
+    }
+
+    fn vars(ticket: Option<&str>) -> Variables {
+        Variables {
+            branch: Some("feat/PF-42-login".to_string()),
+            ticket: ticket.map(str::to_string),
+            date: "2026-10-16".to_string(),
+        }
+    }
+
+    #[test]
+    fn known_placeholders_are_filled() {
+        assert_eq!(
+            expand(
+                "Refs: {ticket}\nBranch: {branch} on {date}",
+                &vars(Some("PF-42"))
+            ),
+            "Refs: PF-42\nBranch: feat/PF-42-login on 2026-10-16"
+        );
+    }
+
+    #[test]
+    fn unknown_placeholders_are_kept() {
+        assert_eq!(
+            expand("Reviewed-by: {reviewer}\nDate: {date}", &vars(None)),
+            "Reviewed-by: {reviewer}\nDate: 2026-10-16"
+        );
+    }
+
+    #[test]
+    fn lines_with_an_empty_ticket_are_left_out() {
+        assert_eq!(
+            expand("Refs: {ticket}\nOn {date}", &vars(None)),
+            "On 2026-10-16"
+        );
+        assert_eq!(expand("Refs: {ticket}", &vars(None)), "");
+    }
+
+    #[test]
+    fn today_is_an_iso_date() {
+        let date = today();
+        assert_eq!(date.len(), 10);
+        assert!(Regex::new(r"^\d{4}-\d{2}-\d{2}$").unwrap().is_match(&date));
+    }
+
+    #[test]
+    fn civil_date_crosses_month_and_leap_day_boundaries() {
+        assert_eq!(civil_date(0), (1970, 1, 1));
+        assert_eq!(civil_date(-1), (1969, 12, 31));
+        // 2024 is a leap year, 2100 is not
+        assert_eq!(civil_date(19_781), (2024, 2, 28));
+        assert_eq!(civil_date(19_782), (2024, 2, 29));
+        assert_eq!(civil_date(19_783), (2024, 3, 1));
+        assert_eq!(civil_date(20_361), (2025, 9, 30));
+        assert_eq!(civil_date(20_362), (2025, 10, 1));
+        assert_eq!(civil_date(47_540), (2100, 2, 28));
+        assert_eq!(civil_date(47_541), (2100, 3, 1));
+    }
+
+    #[test]
+    fn branch_tickets_are_found() {
+        let ticket = |branch| ticket_from_branch(branch);
+        assert_eq!(ticket("feat/PF-42-login"), Some("PF-42".to_string()));
+        assert_eq!(ticket("PF-42"), Some("PF-42".to_string()));
+        assert_eq!(ticket("123-login"), Some("#123".to_string()));
+        assert_eq!(ticket("feat/123-login"), Some("#123".to_string()));
+        assert_eq!(ticket("fix/issue_#7"), Some("#7".to_string()));
+        assert_eq!(ticket("fix/42"), Some("#42".to_string()));
+    }
+
+    #[test]
+    fn digits_inside_words_are_not_tickets() {
+        assert_eq!(ticket_from_branch("feat/oauth2-login"), None);
+        assert_eq!(ticket_from_branch("fix-utf8"), None);
+        assert_eq!(ticket_from_branch("v2"), None);
+        assert_eq!(ticket_from_branch("main"), None);
+    }
+
+    #[test]
+    fn leading_ticket_is_split_off() {
+        let expected = Some(("JIRA-123".to_string(), "add login".to_string()));
+        assert_eq!(split("JIRA-123 add login"), expected);
+        assert_eq!(split("JIRA-123: add login"), expected);
+        assert_eq!(split("[JIRA-123] add login"), expected);
+        assert_eq!(split("JIRA-123 - add login"), expected);
+    }
+
+    #[test]
+    fn descriptions_without_a_leading_ticket_are_kept() {
+        assert_eq!(split("add login for JIRA-123"), None);
+        assert_eq!(split("JIRA-123x add login"), None);
+        assert_eq!(split("[JIRA-123 add login"), None);
+        // stripping would leave no description
+        assert_eq!(split("JIRA-123"), None);
+    }
+}
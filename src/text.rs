@@ -0,0 +1,183 @@
+use unicode_width::UnicodeWidthStr;
+
+/// Re-flow `text` so lines stay within `width` columns where possible.
+///
+/// Existing line breaks are kept, indented lines (code blocks) are left alone,
+/// and words are never split: a token wider than `width`, such as a long URL,
+/// is placed on a line of its own instead.
+pub fn wrap_text(text: &str, width: usize) -> String {
+    let mut out: Vec<String> = Vec::new();
+    for line in text.split('\n') {
+        if line.width() <= width || line.starts_with([' ', '\t']) {
+            out.push(line.to_string());
+            continue;
+        }
+        let mut current = String::new();
+        for word in line.split_whitespace() {
+            if current.is_empty() {
+                current.push_str(word);
+            } else if current.width() + 1 + word.width() <= width {
+                current.push(' ');
+                current.push_str(word);
+            } else {
+                out.push(std::mem::take(&mut current));
+                current.push_str(word);
+            }
+        }
+        out.push(current);
+    }
+    out.join("\n")
+}
+
+/// `body` with a blank line before each list or heading that directly follows
+/// a line of prose, so it reads as a paragraph of its own. Consecutive list
+/// items, indented lines and fenced code blocks are left as they are.
+pub fn separate_blocks(body: &str) -> String {
+    let mut out: Vec<&str> = Vec::new();
+    let mut in_fence = false;
+    for line in body.split('\n') {
+        let fence = line.trim_start().starts_with("```");
+        if !in_fence && let Some(&prev) = out.last() {
+            let after_prose = !prev.trim().is_empty()
+                && !prev.starts_with([' ', '\t'])
+                && !is_list_item(prev)
+                && !prev.trim_start().starts_with("```");
+            if after_prose && (is_list_item(line) || is_heading(line) || fence) {
+                out.push("");
+            }
+        }
+        if fence {
+            in_fence = !in_fence;
+        }
+        out.push(line);
+    }
+    out.join("\n")
+}
+
+fn is_list_item(line: &str) -> bool {
+    line.starts_with("- ") || line.starts_with("* ") || line.starts_with("+ ")
+}
+
+fn is_heading(line: &str) -> bool {
+    let text = line.trim_start_matches('#');
+    text.len() < line.len() && text.starts_with(' ')
+}
+
+/// `text` without surrounding whitespace and, with `collapse`, with every
+/// internal run of whitespace reduced to a single space. For one-line fields.
+pub fn normalize_field(text: &str, collapse: bool) -> String {
+    if collapse {
+        text.split_whitespace().collect::<Vec<_>>().join(" ")
+    } else {
+        text.trim().to_string()
+    }
+}
+
+/// Characters counted against a length limit: all of `text`, or with
+/// `per_line` only the line holding the byte index `cursor`.
+pub fn limited_length(text: &str, cursor: usize, per_line: bool) -> usize {
+    if !per_line {
+        return text.chars().count();
+    }
+    let start = text[..cursor].rfind('\n').map_or(0, |i| i + 1);
+    let end = text[cursor..].find('\n').map_or(text.len(), |i| cursor + i);
+    text[start..end].chars().count()
+}
+
+/// Whether typing one more character at `cursor` stays within `max`.
+pub fn room_for_char(text: &str, cursor: usize, per_line: bool, max: usize) -> bool {
+    limited_length(text, cursor, per_line) < max
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lists_and_headings_after_prose_get_a_blank_line() {
+        assert_eq!(
+            separate_blocks("Changes:\n- one\n- two\nThen more."),
+            "Changes:\n\n- one\n- two\nThen more."
+        );
+        assert_eq!(
+            separate_blocks("Intro line\n## Details\ntext"),
+            "Intro line\n\n## Details\ntext"
+        );
+    }
+
+    #[test]
+    fn separated_or_intentional_formatting_is_kept() {
+        for body in [
+            "First line\nsecond line of the same paragraph",
+            "Intro\n\n- already apart",
+            "    indented code\n- not after prose",
+            "```\nlet x = 1;\n- inside a fence\n```",
+            "see #12 for details\n#hashtag",
+        ] {
+            assert_eq!(separate_blocks(body), body);
+        }
+    }
+
+    #[test]
+    fn surrounding_spaces_are_trimmed() {
+        assert_eq!(normalize_field("  add parser ", false), "add parser");
+        assert_eq!(normalize_field("\tapi\t", false), "api");
+        assert_eq!(normalize_field("   ", false), "");
+    }
+
+    #[test]
+    fn internal_runs_are_kept_unless_collapsing() {
+        assert_eq!(
+            normalize_field(" add   the  parser ", false),
+            "add   the  parser"
+        );
+        assert_eq!(
+            normalize_field(" add   the  parser ", true),
+            "add the parser"
+        );
+    }
+
+    #[test]
+    fn typing_stops_at_the_limit() {
+        assert!(room_for_char("abcd", 4, false, 5));
+        assert!(!room_for_char("abcde", 5, false, 5));
+        assert!(!room_for_char("abcde", 0, false, 5));
+        // characters, not bytes
+        assert!(room_for_char("äöüß", 8, false, 5));
+        assert!(!room_for_char("abcdef", 6, false, 5));
+    }
+
+    #[test]
+    fn per_line_limits_count_only_the_cursor_line() {
+        let body = "abcde\nab\nabcd";
+        assert!(!room_for_char(body, 2, true, 5));
+        assert!(room_for_char(body, 7, true, 5));
+        assert!(room_for_char(body, body.len(), true, 5));
+        assert_eq!(limited_length(body, 6, true), 2);
+        assert_eq!(limited_length(body, 0, false), 13);
+    }
+
+    #[test]
+    fn wraps_at_word_boundaries() {
+        assert_eq!(wrap_text("one two three four", 9), "one two\nthree\nfour");
+    }
+
+    #[test]
+    fn long_url_stays_whole_on_its_own_line() {
+        let url = "https://example.com/a/very/long/path/that/exceeds/the/width";
+        let wrapped = wrap_text(&format!("see {url} for details"), 20);
+        assert_eq!(wrapped, format!("see\n{url}\nfor details"));
+    }
+
+    #[test]
+    fn url_at_start_of_line_is_not_split() {
+        let url = "https://example.com/0123456789/0123456789";
+        assert_eq!(wrap_text(url, 10), url);
+    }
+
+    #[test]
+    fn indented_lines_and_breaks_are_kept() {
+        let text = "short\n\n    let x = some_really_long_function_call(argument);";
+        assert_eq!(wrap_text(text, 20), text);
+    }
+}
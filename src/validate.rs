@@ -0,0 +1,528 @@
+use regex::Regex;
+use serde::Serialize;
+
+use crate::config::{Rule, Severity};
+use crate::git;
+use crate::message::{is_git_generated, parse_commit_message};
+use crate::{App, Focus, TextInput};
+
+/// A rule the current message breaks, tied to the field that needs fixing.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Issue {
+    pub field: Focus,
+    pub message: String,
+    /// `Error` blocks the message, `Warn` only points the problem out.
+    pub severity: Severity,
+}
+
+impl Issue {
+    /// A problem that always blocks, outside any `[severity]` rule.
+    pub fn error(field: Focus, message: impl Into<String>) -> Issue {
+        Issue {
+            field,
+            message: message.into(),
+            severity: Severity::Error,
+        }
+    }
+}
+
+/// Add an issue for a broken rule, as its severity says: `Off` drops it.
+fn report(issues: &mut Vec<Issue>, severity: Severity, field: Focus, message: String) {
+    if severity != Severity::Off {
+        issues.push(Issue {
+            field,
+            message,
+            severity,
+        });
+    }
+}
+
+/// The first issue that keeps the message from being written.
+pub fn first_error(issues: Vec<Issue>) -> Option<Issue> {
+    issues
+        .into_iter()
+        .find(|issue| issue.severity == Severity::Error)
+}
+
+/// Check the form against the configured rules; the message may be written
+/// unless one of them is an error.
+pub fn validate(app: &App) -> Vec<Issue> {
+    let mut issues = Vec::new();
+    let severity = |rule| app.config.severity(rule);
+    let commit_type = &app.types[app.type_idx];
+    // freeform messages have no type, so its rules don't apply
+    let conventional = !app.config.freeform;
+    if conventional && commit_type.require_scope && app.scope.value.trim().is_empty() {
+        report(
+            &mut issues,
+            severity(Rule::RequireScope),
+            Focus::Scope,
+            format!("`{}` commits need a scope", commit_type.name),
+        );
+    }
+    let scope = app.normalized_scope();
+    if conventional && !scope_allowed(&commit_type.allowed_scopes, &scope) {
+        report(
+            &mut issues,
+            severity(Rule::AllowedScopes),
+            Focus::Scope,
+            format!(
+                "`{}` commits allow only the scopes {}",
+                commit_type.name,
+                commit_type
+                    .allowed_scopes
+                    .iter()
+                    .map(|s| format!("`{s}`"))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+        );
+    }
+    let ticket = app.ticket.value.trim();
+    if let Some(regex) = app.config.ticket.as_ref().and_then(|t| t.regex.as_ref())
+        && !ticket.is_empty()
+        && !regex.is_match(ticket)
+    {
+        report(
+            &mut issues,
+            severity(Rule::TicketFormat),
+            Focus::Ticket,
+            format!("ticket must match `{}`", regex.as_str()),
+        );
+    }
+    if let Some(max) = app.config.header_max_length {
+        let message = app.commit_message();
+        let length = message.lines().next().unwrap_or("").chars().count();
+        if length > max {
+            report(
+                &mut issues,
+                severity(Rule::HeaderLength),
+                Focus::Description,
+                format!("header is {length} characters, the limit is {max}"),
+            );
+        }
+    }
+    // hard limits stop typing, but pasted or prefilled text can still be over
+    for (field, what) in [
+        (Focus::Scope, "scope"),
+        (Focus::Description, "description"),
+        (Focus::Body, "a body line"),
+    ] {
+        if let Some((input, per_line, limit)) = app.length_limit(field)
+            && limit.hard
+        {
+            let length = if per_line {
+                input
+                    .value
+                    .lines()
+                    .map(|l| l.chars().count())
+                    .max()
+                    .unwrap_or(0)
+            } else {
+                input.value.chars().count()
+            };
+            if length > limit.max {
+                report(
+                    &mut issues,
+                    severity(Rule::MaxLength),
+                    field,
+                    format!("{what} is {length} characters, the limit is {}", limit.max),
+                );
+            }
+        }
+    }
+    if let Some(pattern) = &app.config.footer_regex
+        && app.field_enabled(Focus::Footer)
+        && !footer_matches(&app.footer_text(), pattern)
+    {
+        report(
+            &mut issues,
+            severity(Rule::FooterFormat),
+            Focus::Footer,
+            format!("footer must contain a line matching `{}`", pattern.as_str()),
+        );
+    }
+    if conventional
+        && app.config.require_breaking_explanation
+        && app.breaking
+        && !breaking_explained(&app.body.value, &app.footer_text())
+    {
+        // point at whichever field can still take the explanation
+        let field = if app.field_enabled(Focus::Body) {
+            Focus::Body
+        } else {
+            Focus::Footer
+        };
+        report(
+            &mut issues,
+            severity(Rule::BreakingExplanation),
+            field,
+            "breaking changes need a body or a `BREAKING CHANGE:` footer".to_string(),
+        );
+    }
+    issues
+}
+
+/// `validate_message` for a freeform message: the first line is the subject,
+/// the rest the body.
+fn validate_freeform(lines: &[(usize, &str)], header_line: usize, app: &mut App) -> Vec<Violation> {
+    let subject = lines.first().map_or("", |(_, l)| *l);
+    let body_at = lines
+        .iter()
+        .skip(1)
+        .find(|(_, l)| !l.trim().is_empty())
+        .map_or(header_line, |(n, _)| *n);
+    let body: Vec<&str> = lines.iter().skip(1).map(|(_, l)| *l).collect();
+    app.description = TextInput::from(subject.to_string());
+    app.body = TextInput::from(body.join("\n").trim_matches('\n').to_string());
+    validate(app)
+        .into_iter()
+        .map(|issue| Violation {
+            line: if issue.field == Focus::Body {
+                body_at
+            } else {
+                header_line
+            },
+            column: 1,
+            message: issue.message,
+            severity: issue.severity,
+        })
+        .collect()
+}
+
+/// Whether a type limited to `allowed` scopes may use `scope`. No scope is
+/// always allowed; `require_scope` covers that.
+fn scope_allowed(allowed: &[String], scope: &str) -> bool {
+    allowed.is_empty() || scope.is_empty() || allowed.iter().any(|a| a == scope)
+}
+
+/// Whether a breaking change says what breaks: a non-blank body or a
+/// `BREAKING CHANGE:` (or `BREAKING-CHANGE:`) footer.
+fn breaking_explained(body: &str, footer: &str) -> bool {
+    !body.trim().is_empty()
+        || footer
+            .lines()
+            .any(|l| l.starts_with("BREAKING CHANGE:") || l.starts_with("BREAKING-CHANGE:"))
+}
+
+/// A broken rule found by `pre-form validate`, with its 1-based position in
+/// the message file.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Violation {
+    pub line: usize,
+    pub column: usize,
+    pub message: String,
+    /// `error` fails the check, `warn` is only reported.
+    pub severity: Severity,
+}
+
+/// Check a complete message, as Git would write it, against the same rules as
+/// the form. Comment lines are ignored. The form in `app` is replaced.
+pub fn validate_message(raw: &str, app: &mut App) -> Vec<Violation> {
+    // keep the original line numbers for reporting
+    let comment = git::comment_prefix(raw);
+    let lines: Vec<(usize, &str)> = raw
+        .lines()
+        .enumerate()
+        .filter(|(_, l)| !l.starts_with(comment.as_str()))
+        .map(|(i, l)| (i + 1, l))
+        .collect();
+    let text: Vec<&str> = lines.iter().map(|(_, l)| *l).collect();
+    let header_at = lines
+        .iter()
+        .position(|(_, l)| !l.trim().is_empty())
+        .unwrap_or(0);
+    let header_line = lines.get(header_at).map_or(1, |(n, _)| *n);
+    // merges, reverts and autosquash commits are worded by Git
+    if lines
+        .get(header_at)
+        .is_some_and(|(_, header)| is_git_generated(header))
+    {
+        return Vec::new();
+    }
+    // a custom header or layout can't be read back into fields, so only the
+    // rules a freeform message has apply
+    if app.config.header_template.is_some() || app.config.commit_template.is_some() {
+        app.config.freeform = true;
+        // the subject is checked as written, not laid out again
+        app.config.commit_template_text = None;
+    }
+    if app.config.freeform {
+        return validate_freeform(&lines[header_at.min(lines.len())..], header_line, app);
+    }
+    let Some(parsed) = parse_commit_message(text.join("\n").trim(), app.config.scope_format) else {
+        return vec![Violation {
+            line: header_line,
+            column: 1,
+            message: format!(
+                "header must look like `type{}: description`",
+                app.config.scope_format.wrap("scope")
+            ),
+            severity: Severity::Error,
+        }];
+    };
+    // the message already carries its ticket, if any
+    app.ticket = TextInput::new();
+    app.body = TextInput::new();
+    app.set_footer("");
+    if let Err(message) = app.apply_parsed(&parsed) {
+        return vec![Violation {
+            line: header_line,
+            column: 1,
+            message,
+            severity: Severity::Error,
+        }];
+    }
+
+    // where each field starts in the file
+    let header = lines[header_at].1;
+    let line_of = |first: &str, from_end: bool| {
+        let first = first.lines().next().unwrap_or("");
+        let rest = &lines[header_at + 1..];
+        let found = if from_end {
+            rest.iter().rev().find(|(_, l)| *l == first)
+        } else {
+            rest.iter().find(|(_, l)| *l == first)
+        };
+        found.map_or(header_line, |(n, _)| *n)
+    };
+    let locate = |field: Focus| -> (usize, usize) {
+        match field {
+            // the ticket isn't read back from a message; point at the header
+            Focus::Type | Focus::Ticket => (header_line, 1),
+            Focus::Scope => (header_line, parsed.commit_type.chars().count() + 1),
+            Focus::Description => (
+                header_line,
+                header
+                    .find(": ")
+                    .map_or(1, |i| header[..i].chars().count() + 3),
+            ),
+            Focus::Body => (line_of(&parsed.body, false), 1),
+            Focus::Footer if parsed.footer.is_empty() => (lines.last().map_or(1, |(n, _)| *n), 1),
+            Focus::Footer => (line_of(&parsed.footer, true), 1),
+        }
+    };
+
+    let mut issues = Vec::new();
+    let commit_type = &app.types[app.type_idx];
+    if commit_type.forbid_body && !parsed.body.is_empty() {
+        issues.push(Issue::error(
+            Focus::Body,
+            format!("`{}` commits must not have a body", commit_type.name),
+        ));
+    }
+    if commit_type.forbid_footer && !parsed.footer.is_empty() {
+        issues.push(Issue::error(
+            Focus::Footer,
+            format!("`{}` commits must not have a footer", commit_type.name),
+        ));
+    }
+    issues.extend(validate(app));
+    let mut violations: Vec<Violation> = issues
+        .into_iter()
+        .map(|issue| {
+            let (line, mut column) = locate(issue.field);
+            // point at the first character past the limit
+            if let Some(max) = app.config.header_max_length
+                && issue.field == Focus::Description
+                && header.chars().count() > max
+            {
+                column = max + 1;
+            }
+            Violation {
+                line,
+                column,
+                message: issue.message,
+                severity: issue.severity,
+            }
+        })
+        .collect();
+    violations.sort_by_key(|v| (v.line, v.column));
+    violations
+}
+
+/// Whether any footer line matches `pattern`.
+pub fn footer_matches(footer: &str, pattern: &Regex) -> bool {
+    footer.lines().any(|line| pattern.is_match(line))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::CommitType;
+    use crate::config::{Config, ScopeFormat};
+    use std::path::PathBuf;
+
+    /// A form writing `feat` commits as `config` says.
+    fn app(config: Config) -> App {
+        let types = vec![CommitType::new("feat".to_string(), &config)];
+        App::offline(config, PathBuf::new(), types)
+    }
+
+    #[test]
+    fn messages_in_every_scope_format_validate() {
+        for (scope_format, header) in [
+            (ScopeFormat::Parens, "feat(api): add login"),
+            (ScopeFormat::Brackets, "feat[api]: add login"),
+            (ScopeFormat::Slash, "feat/api: add login"),
+        ] {
+            let mut app = app(Config {
+                scope_format,
+                ..Config::default()
+            });
+            app.scope = TextInput::from("api".to_string());
+            app.description = TextInput::from("add login".to_string());
+            let message = app.commit_message();
+            assert_eq!(message, header);
+            assert_eq!(validate_message(&message, &mut app), Vec::new(), "{header}");
+            assert_eq!(app.scope.value, "api");
+        }
+    }
+
+    #[test]
+    fn git_generated_messages_pass() {
+        for message in [
+            "Merge branch 'main' into feat/login\n",
+            "Merge pull request #12 from ann/login\n\nAdd login",
+            "Revert \"feat: add login\"\n\nThis reverts commit a1b2c3d.\n",
+            "fixup! feat: add login\n",
+            "squash! feat: add login\n\nfold the tests in",
+            "amend! feat: add login\n\nfeat: add a login form\n",
+        ] {
+            let mut app = app(Config::default());
+            assert_eq!(validate_message(message, &mut app), Vec::new(), "{message}");
+        }
+        let mut app = app(Config::default());
+        assert_eq!(validate_message("Merged it\n", &mut app).len(), 1);
+    }
+
+    #[test]
+    fn templated_header_is_checked_as_freeform() {
+        let mut app = app(Config {
+            header_template: Some("{emoji} {description}".to_string()),
+            header_max_length: Some(20),
+            ..Config::default()
+        });
+        app.description = TextInput::from("add login".to_string());
+        let message = app.commit_message();
+        assert_eq!(validate_message(&message, &mut app), Vec::new());
+        let long = "✨ add login with a remembered session";
+        let violations = validate_message(long, &mut app);
+        assert_eq!(violations.len(), 1);
+        assert_eq!(
+            violations[0].message,
+            "header is 37 characters, the limit is 20"
+        );
+    }
+
+    #[test]
+    fn commit_template_layout_is_checked_as_freeform() {
+        let mut app = app(Config {
+            commit_template: Some(PathBuf::from("commit-template")),
+            commit_template_text: Some("[{scope}] {description}\n\n{body}\n\n{footer}".to_string()),
+            header_max_length: Some(30),
+            ..Config::default()
+        });
+        app.scope = TextInput::from("api".to_string());
+        app.description = TextInput::from("add login".to_string());
+        app.body = TextInput::from("Sessions last a day.".to_string());
+        app.set_footer("Refs: #12");
+        let message = app.commit_message();
+        assert!(message.starts_with("[api] add login\n"));
+        assert_eq!(validate_message(&message, &mut app), Vec::new());
+    }
+
+    #[test]
+    fn footer_with_matching_line_passes() {
+        let pattern = Regex::new(r"^Refs: #\d+$").unwrap();
+        assert!(footer_matches("Refs: #42", &pattern));
+        assert!(footer_matches("Reviewed-by: Ann\nRefs: #42", &pattern));
+    }
+
+    #[test]
+    fn footer_without_matching_line_fails() {
+        let pattern = Regex::new(r"^Refs: #\d+$").unwrap();
+        assert!(!footer_matches("", &pattern));
+        assert!(!footer_matches("Refs: 42", &pattern));
+        assert!(!footer_matches("see Refs: #42", &pattern));
+    }
+
+    #[test]
+    fn error_blocks_and_warning_does_not() {
+        let mut issues = Vec::new();
+        report(&mut issues, Severity::Warn, Focus::Scope, "warned".into());
+        assert_eq!(first_error(issues.clone()), None);
+        report(
+            &mut issues,
+            Severity::Error,
+            Focus::Footer,
+            "blocked".into(),
+        );
+        assert_eq!(issues.len(), 2);
+        assert_eq!(first_error(issues).unwrap().message, "blocked");
+    }
+
+    #[test]
+    fn configured_severity_reaches_validate() {
+        let header_issues = |severity: Option<Severity>| {
+            let mut config = Config {
+                header_max_length: Some(10),
+                ..Config::default()
+            };
+            if let Some(severity) = severity {
+                config.severity.insert(Rule::HeaderLength, severity);
+            }
+            let mut app = app(config);
+            app.description = TextInput::from("add a login form".to_string());
+            validate(&app)
+        };
+        let errors = header_issues(None);
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].severity, Severity::Error);
+        let warnings = header_issues(Some(Severity::Warn));
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(warnings[0].severity, Severity::Warn);
+        assert_eq!(first_error(warnings), None);
+        assert_eq!(header_issues(Some(Severity::Off)), Vec::new());
+    }
+
+    #[test]
+    fn off_drops_the_issue() {
+        let mut issues = Vec::new();
+        report(&mut issues, Severity::Off, Focus::Scope, "ignored".into());
+        assert!(issues.is_empty());
+    }
+
+    #[test]
+    fn listed_scope_is_allowed() {
+        let allowed = ["github".to_string(), "gitlab".to_string()];
+        assert!(scope_allowed(&allowed, "github"));
+        assert!(scope_allowed(&allowed, "gitlab"));
+        assert!(scope_allowed(&allowed, ""));
+        assert!(scope_allowed(&[], "anything"));
+    }
+
+    #[test]
+    fn unlisted_scope_is_refused() {
+        let allowed = ["github".to_string(), "gitlab".to_string()];
+        assert!(!scope_allowed(&allowed, "jenkins"));
+        assert!(!scope_allowed(&allowed, "GitHub"));
+    }
+
+    #[test]
+    fn breaking_change_with_explanation_passes() {
+        assert!(breaking_explained("Drops the v1 endpoints.", ""));
+        assert!(breaking_explained("", "BREAKING CHANGE: v1 is gone"));
+        assert!(breaking_explained(
+            "",
+            "Refs: #1\nBREAKING-CHANGE: v1 is gone"
+        ));
+    }
+
+    #[test]
+    fn breaking_change_without_explanation_fails() {
+        assert!(!breaking_explained("", ""));
+        assert!(!breaking_explained("  \n", "Refs: #1"));
+        assert!(!breaking_explained("", "see BREAKING CHANGE: below"));
+    }
+}